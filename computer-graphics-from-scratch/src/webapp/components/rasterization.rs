@@ -500,9 +500,9 @@ impl Renderer {
         let transform = self.camera.get_orientation().transpose() * instance.get_orientation();
 
         let (n0, n1, n2) = (triangle.normals[i0], triangle.normals[i1], triangle.normals[i2]);
-        let n0 = transform * vec4(n0.x, n0.y, n0.z, 1.0);
-        let n1 = transform * vec4(n1.x, n1.y, n1.z, 1.0);
-        let n2 = transform * vec4(n2.x, n2.y, n2.z, 1.0);
+        let n0 = transform * vec4(n0.x, n0.y, n0.z, 0.0);
+        let n1 = transform * vec4(n1.x, n1.y, n1.z, 0.0);
+        let n2 = transform * vec4(n2.x, n2.y, n2.z, 0.0);
 
         let (nx02, nx012) = self.edge_interpolate(p0.y, n0.x, p1.y, n1.x, p2.y, n2.x);
         let (ny02, ny012) = self.edge_interpolate(p0.y, n0.y, p1.y, n1.y, p2.y, n2.y);
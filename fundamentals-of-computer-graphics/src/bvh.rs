@@ -0,0 +1,419 @@
+//! Bounding volume hierarchy over surfaces (FCG 12.3.2), built top-down with
+//! the binned surface area heuristic and flattened into depth-first order
+//! for traversal (PBRT 4.3).
+
+use crate::math::{BoundingBox, Vector3};
+use crate::raytracing::{HitRecord, Ray, Surface, SurfaceGroup};
+
+/// Centroid bins per axis when looking for a split.
+const BINS: usize = 16;
+
+/// Leaves with more surfaces than this are split even when the heuristic
+/// does not pay off.
+const MAX_LEAF_SIZE: usize = 4;
+
+/// Cost of visiting a node relative to testing one surface.
+const TRAVERSAL_COST: f32 = 0.5;
+
+/// A node in depth-first order: an interior node's first child follows it
+/// directly, so only the second one needs an index.
+#[derive(Debug, Copy, Clone)]
+struct Node {
+    bounds: BoundingBox,
+    /// First surface of a leaf, or the second child of an interior node.
+    offset: u32,
+    /// Surfaces in a leaf, 0 for interior nodes.
+    count: u32,
+    /// Split axis of an interior node.
+    axis: u8,
+}
+
+#[derive(PartialEq, Eq, Debug, Copy, Clone, Default)]
+pub struct BvhStats {
+    pub nodes: usize,
+    pub leaves: usize,
+    /// Levels from the root to the deepest leaf, 1 for a lone leaf.
+    pub depth: usize,
+}
+
+/// A BVH is itself a `Surface`, so it can go into a `SurfaceGroup` next to
+/// planes and other unbounded surfaces, which are kept out of the tree and
+/// tested one by one.
+pub struct Bvh {
+    surfaces: Vec<Box<dyn Surface>>,
+    nodes: Vec<Node>,
+    unbounded: Vec<Box<dyn Surface>>,
+    depth: usize,
+}
+
+/// Bounds and centroid of a surface during the build.
+struct Item {
+    index: usize,
+    bounds: BoundingBox,
+    centroid: Vector3,
+}
+
+impl Bvh {
+    pub fn new(surfaces: Vec<Box<dyn Surface>>) -> Self {
+        let mut bounded = vec![];
+        let mut unbounded = vec![];
+        for surface in surfaces {
+            let b = surface.bounding_box();
+            if (0..3).all(|i| b.min[i].is_finite() && b.max[i].is_finite()) {
+                bounded.push(Some(surface));
+            } else {
+                unbounded.push(surface);
+            }
+        }
+
+        let mut items: Vec<Item> = bounded
+            .iter()
+            .enumerate()
+            .map(|(index, s)| {
+                let bounds = s.as_ref().unwrap().bounding_box();
+                Item { index, bounds, centroid: bounds.centroid() }
+            })
+            .collect();
+
+        let mut bvh = Self {
+            surfaces: Vec::with_capacity(items.len()),
+            nodes: Vec::with_capacity(2 * items.len()),
+            unbounded,
+            depth: 0,
+        };
+        if !items.is_empty() {
+            bvh.depth = bvh.build(&mut items, 0, 1);
+        }
+
+        // surfaces in leaf order, so each leaf is a contiguous range
+        bvh.surfaces = items.iter().map(|item| bounded[item.index].take().unwrap()).collect();
+        bvh
+    }
+
+    /// Appends the subtree over `items`, which start at `start` in the final
+    /// surface order, and returns its depth. `items` end up reordered so each
+    /// leaf covers a contiguous range.
+    fn build(&mut self, items: &mut [Item], start: usize, level: usize) -> usize {
+        let bounds = items.iter().fold(BoundingBox::EMPTY, |b, item| b.union(&item.bounds));
+        let node = self.nodes.len();
+        self.nodes.push(Node { bounds, offset: start as u32, count: items.len() as u32, axis: 0 });
+
+        let Some((axis, mid)) = split(items, &bounds) else {
+            return level;
+        };
+
+        self.nodes[node].count = 0;
+        self.nodes[node].axis = axis as u8;
+
+        let (left, right) = items.split_at_mut(mid);
+        let left_depth = self.build(left, start, level + 1);
+        self.nodes[node].offset = self.nodes.len() as u32;
+        let right_depth = self.build(right, start + mid, level + 1);
+        left_depth.max(right_depth)
+    }
+
+    pub fn stats(&self) -> BvhStats {
+        BvhStats {
+            nodes: self.nodes.len(),
+            leaves: self.nodes.iter().filter(|n| n.count > 0).count(),
+            depth: self.depth,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.surfaces.len() + self.unbounded.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Visits the leaves the ray may hit in `[t0, t1]`, near child first.
+    /// `visit` returns a new `t1`, or `None` to stop.
+    fn traverse(&self, ray: &Ray, t0: f32, mut t1: f32, mut visit: impl FnMut(&[Box<dyn Surface>], f32) -> Option<f32>) {
+        if self.nodes.is_empty() {
+            return;
+        }
+
+        let negative = [ray.d.x < 0., ray.d.y < 0., ray.d.z < 0.];
+        let mut stack = Vec::with_capacity(self.depth);
+        let mut current = 0;
+        loop {
+            let node = &self.nodes[current];
+            if node.bounds.intersect_ray(&ray.e, &ray.d, t0, t1).is_some() {
+                if node.count > 0 {
+                    let range = node.offset as usize..node.offset as usize + node.count as usize;
+                    match visit(&self.surfaces[range], t1) {
+                        Some(t) => t1 = t,
+                        None => return,
+                    }
+                } else {
+                    let (near, far) = if negative[node.axis as usize] {
+                        (node.offset as usize, current + 1)
+                    } else {
+                        (current + 1, node.offset as usize)
+                    };
+                    stack.push(far);
+                    current = near;
+                    continue;
+                }
+            }
+
+            match stack.pop() {
+                Some(next) => current = next,
+                None => return,
+            }
+        }
+    }
+}
+
+/// Index of the axis and the position splitting `items` by the surface area
+/// heuristic, with `items` partitioned accordingly; `None` when a leaf is
+/// cheaper or the centroids cannot be told apart.
+fn split(items: &mut [Item], bounds: &BoundingBox) -> Option<(usize, usize)> {
+    let n = items.len();
+    if n == 1 {
+        return None;
+    }
+
+    let centroids = items.iter().fold(BoundingBox::EMPTY, |b, item| b.grow(&item.centroid));
+    let axis = centroids.longest_axis();
+    let (lo, extent) = (centroids.min[axis], centroids.max[axis] - centroids.min[axis]);
+    if extent == 0. {
+        return None;
+    }
+
+    let bin = |item: &Item| (((item.centroid[axis] - lo) / extent * BINS as f32) as usize).min(BINS - 1);
+    let mut counts = [0usize; BINS];
+    let mut boxes = [BoundingBox::EMPTY; BINS];
+    for item in items.iter() {
+        let b = bin(item);
+        counts[b] += 1;
+        boxes[b] = boxes[b].union(&item.bounds);
+    }
+
+    // sweep from the right for the suffix areas, then from the left
+    let mut right_area = [0.; BINS];
+    let (mut b, mut c) = (BoundingBox::EMPTY, 0);
+    let mut right_count = [0; BINS];
+    for i in (1..BINS).rev() {
+        b = b.union(&boxes[i]);
+        c += counts[i];
+        right_area[i] = b.surface_area();
+        right_count[i] = c;
+    }
+
+    let (mut b, mut c) = (BoundingBox::EMPTY, 0);
+    let mut best = (f32::INFINITY, 0);
+    for i in 1..BINS {
+        b = b.union(&boxes[i - 1]);
+        c += counts[i - 1];
+        if c == 0 || right_count[i] == 0 {
+            continue;
+        }
+        let cost = TRAVERSAL_COST + (b.surface_area() * c as f32 + right_area[i] * right_count[i] as f32) / bounds.surface_area();
+        if cost < best.0 {
+            best = (cost, i);
+        }
+    }
+
+    if n <= MAX_LEAF_SIZE && best.0 >= n as f32 {
+        return None;
+    }
+
+    if best.1 != 0 {
+        let mut mid = 0;
+        for i in 0..n {
+            if bin(&items[i]) < best.1 {
+                items.swap(i, mid);
+                mid += 1;
+            }
+        }
+        if mid != 0 && mid != n {
+            return Some((axis, mid));
+        }
+    }
+
+    // no finite cost, as for a node of zero or infinite area: halve at the
+    // median centroid so the recursion still ends
+    let mid = n / 2;
+    items.select_nth_unstable_by(mid, |a, b| a.centroid[axis].total_cmp(&b.centroid[axis]));
+    Some((axis, mid))
+}
+
+impl From<SurfaceGroup> for Bvh {
+    fn from(group: SurfaceGroup) -> Self {
+        Self::new(group.into_surfaces())
+    }
+}
+
+impl Surface for Bvh {
+    fn hit(&self, ray: &Ray, t0: f32, t1: f32) -> Option<HitRecord> {
+        let mut closest: Option<HitRecord> = None;
+        let mut t1 = t1;
+        for surface in &self.unbounded {
+            if let Some(hit) = surface.hit(ray, t0, t1) {
+                t1 = hit.t;
+                closest = Some(hit);
+            }
+        }
+
+        self.traverse(ray, t0, t1, |surfaces, mut t1| {
+            for surface in surfaces {
+                if let Some(hit) = surface.hit(ray, t0, t1) {
+                    t1 = hit.t;
+                    closest = Some(hit);
+                }
+            }
+            Some(t1)
+        });
+        closest
+    }
+
+    fn any_hit(&self, ray: &Ray, t0: f32, t1: f32) -> bool {
+        if self.unbounded.iter().any(|s| s.any_hit(ray, t0, t1)) {
+            return true;
+        }
+
+        let mut blocked = false;
+        self.traverse(ray, t0, t1, |surfaces, t1| {
+            blocked = surfaces.iter().any(|s| s.any_hit(ray, t0, t1));
+            (!blocked).then_some(t1)
+        });
+        blocked
+    }
+
+    fn bounding_box(&self) -> BoundingBox {
+        let bounds = self.nodes.first().map_or(BoundingBox::EMPTY, |root| root.bounds);
+        self.unbounded.iter().fold(bounds, |b, s| b.union(&s.bounding_box()))
+    }
+}
+
+#[cfg(test)]
+mod test_bvh {
+    use super::*;
+    use crate::math::{vec3, Vector3};
+    use crate::raytracing::{Plane, Sphere, TriangleSurface};
+    use crate::sampling::Rng;
+
+    fn random_point(rng: &mut Rng, size: f32) -> Vector3 {
+        &vec3(rng.next_f32() - 0.5, rng.next_f32() - 0.5, rng.next_f32() - 0.5) * size
+    }
+
+    /// The same random spheres and triangles on every call.
+    fn surfaces(n: usize) -> Vec<Box<dyn Surface>> {
+        let mut rng = Rng::new(7);
+        let mut surfaces: Vec<Box<dyn Surface>> = vec![];
+        for i in 0..n {
+            let p = random_point(&mut rng, 20.);
+            if i % 2 == 0 {
+                surfaces.push(Box::new(Sphere::new(p, 0.1 + rng.next_f32(), i)));
+            } else {
+                let (a, b) = (random_point(&mut rng, 3.), random_point(&mut rng, 3.));
+                surfaces.push(Box::new(TriangleSurface::new(p, &p + &a, &p + &b, i)));
+            }
+        }
+        surfaces
+    }
+
+    fn group(surfaces: Vec<Box<dyn Surface>>) -> SurfaceGroup {
+        let mut group = SurfaceGroup::new();
+        for surface in surfaces {
+            group.push_boxed(surface);
+        }
+        group
+    }
+
+    #[test]
+    fn test_against_brute_force() {
+        for n in [1, 2, 5, 100, 1000] {
+            let bvh = Bvh::new(surfaces(n));
+            let brute = group(surfaces(n));
+            assert_eq!(n, bvh.len());
+
+            let mut rng = Rng::new(11);
+            let (mut hits, mut blocked) = (0, 0);
+            for _ in 0..1000 {
+                // from around the scene towards somewhere inside it
+                let e = random_point(&mut rng, 40.);
+                let d = &random_point(&mut rng, 10.) - &e;
+                let ray = Ray::new(e, d);
+                let t1 = rng.next_f32() + 0.5;
+
+                let expected = brute.hit(&ray, 0., t1);
+                assert_eq!(expected, bvh.hit(&ray, 0., t1), "{:?}", ray);
+                assert_eq!(brute.any_hit(&ray, 0., t1), bvh.any_hit(&ray, 0., t1));
+
+                hits += expected.is_some() as usize;
+                blocked += bvh.any_hit(&ray, 0., t1) as usize;
+            }
+            assert_eq!(hits, blocked);
+            if n >= 100 {
+                assert!(hits > n / 10, "{hits}");
+            }
+
+            assert_eq!(brute.bounding_box(), bvh.bounding_box());
+        }
+    }
+
+    #[test]
+    fn test_unbounded() {
+        let mut surfaces = surfaces(50);
+        surfaces.push(Box::new(Plane::new(vec3(0., -8., 0.), &vec3(0., 1., 0.), 99)));
+        let bvh = Bvh::new(surfaces);
+        assert_eq!(51, bvh.len());
+
+        let hit = bvh.hit(&Ray::new(vec3(100., 0., 0.), vec3(0., -1., 0.)), 0., f32::INFINITY).unwrap();
+        assert_eq!((8., 99), (hit.t, hit.material));
+        assert!(bvh.bounding_box().contains(&vec3(1e30, -1e30, 0.)));
+
+        let empty = Bvh::new(vec![]);
+        assert!(empty.is_empty());
+        assert_eq!(None, empty.hit(&Ray::new(vec3(0., 0., 0.), vec3(0., 0., 1.)), 0., f32::INFINITY));
+        assert_eq!(BvhStats::default(), empty.stats());
+    }
+
+    #[test]
+    fn test_stats() {
+        let bvh = Bvh::new(surfaces(1000));
+        let stats = bvh.stats();
+
+        // a binary tree, with leaves of a few surfaces and a reasonable depth
+        assert_eq!(2 * stats.leaves - 1, stats.nodes);
+        assert!(stats.leaves >= 1000 / MAX_LEAF_SIZE, "{:?}", stats);
+        assert!((10..40).contains(&stats.depth), "{:?}", stats);
+
+        let one = Bvh::new(surfaces(1)).stats();
+        assert_eq!(BvhStats { nodes: 1, leaves: 1, depth: 1 }, one);
+
+        // identical surfaces cannot be split and share one leaf
+        let same: Vec<Box<dyn Surface>> = (0..10).map(|i| Box::new(Sphere::new(vec3(0., 0., 0.), 1., i)) as Box<dyn Surface>).collect();
+        assert_eq!(BvhStats { nodes: 1, leaves: 1, depth: 1 }, Bvh::new(same).stats());
+    }
+
+    #[test]
+    fn test_degenerate_bounds() {
+        // collinear zero-area triangles give nodes of zero area, and huge
+        // spheres nodes of infinite area, both making every SAH cost NaN
+        let collinear: Vec<Box<dyn Surface>> = (0..20)
+            .map(|i| {
+                let x = i as f32;
+                Box::new(TriangleSurface::new(vec3(x, 0., 0.), vec3(x + 0.5, 0., 0.), vec3(x + 0.25, 0., 0.), i)) as Box<dyn Surface>
+            })
+            .collect();
+        let huge: Vec<Box<dyn Surface>> = (0..20)
+            .map(|i| Box::new(Sphere::new(vec3(i as f32 * 1e18, 0., 0.), 1e19, i)) as Box<dyn Surface>)
+            .collect();
+
+        for surfaces in [collinear, huge] {
+            let bvh = Bvh::new(surfaces);
+            let stats = bvh.stats();
+            assert_eq!(20, bvh.len());
+            assert_eq!(2 * stats.leaves - 1, stats.nodes);
+            assert!(stats.depth <= 10, "{:?}", stats);
+        }
+
+        let huge = Bvh::new(vec![Box::new(Sphere::new(vec3(0., 0., 0.), 1e19, 0)), Box::new(Sphere::new(vec3(1e18, 0., 0.), 1e19, 1))]);
+        assert!(huge.hit(&Ray::new(vec3(0., 0., 0.), vec3(1., 0., 0.)), 0., f32::INFINITY).is_some());
+    }
+}
@@ -0,0 +1,177 @@
+//! Ray-generating cameras (FCG 4.3).
+//!
+//! An image of `nx` by `ny` pixels covers the rectangle `[l, r] x [b, t]` of
+//! the image plane, pixel `(i, j)` spanning `[i, i + 1) x [j, j + 1)` in
+//! pixel units with `j = 0` at the bottom, as in `Framebuffer`.
+
+use crate::math::{OrthonormalBasis, Vector2, Vector3};
+use crate::raytracing::Ray;
+
+pub trait Camera {
+    /// Ray through the image point `(x, y)` in pixel units, e.g. `(0, 0)` is
+    /// the bottom-left corner of the image and `(nx, ny)` the top-right one.
+    fn ray_through(&self, x: f32, y: f32) -> Ray;
+
+    /// Ray through the center of pixel `(i, j)`.
+    fn ray(&self, i: usize, j: usize) -> Ray {
+        self.ray_through(i as f32 + 0.5, j as f32 + 0.5)
+    }
+
+    /// Ray through pixel `(i, j)` at `offset` in `[0, 1)^2` from its
+    /// bottom-left corner, e.g. for jittered supersampling.
+    fn ray_with_offset(&self, i: usize, j: usize, offset: &Vector2) -> Ray {
+        self.ray_through(i as f32 + offset.x, j as f32 + offset.y)
+    }
+}
+
+/// Parallel rays along `gaze`, starting on a `width` by `height` viewport
+/// centered on the eye.
+#[derive(PartialEq, Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OrthographicCamera {
+    pub eye: Vector3,
+    pub basis: OrthonormalBasis,
+    pub nx: usize,
+    pub ny: usize,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl OrthographicCamera {
+    pub fn new(eye: Vector3, gaze: &Vector3, up: &Vector3, nx: usize, ny: usize, width: f32, height: f32) -> Self {
+        Self {
+            eye,
+            basis: OrthonormalBasis::from_two_vectors(&-gaze, up),
+            nx,
+            ny,
+            width,
+            height,
+        }
+    }
+}
+
+impl Camera for OrthographicCamera {
+    fn ray_through(&self, x: f32, y: f32) -> Ray {
+        let (u, v) = image_plane(x, y, self.nx, self.ny, self.width / 2., self.height / 2.);
+        let OrthonormalBasis { u: bu, v: bv, w } = &self.basis;
+
+        Ray::new(&(&self.eye + &(u * bu)) + &(v * bv), -w)
+    }
+}
+
+/// Rays from the eye through an image plane `focal_length` in front of it,
+/// spanning the vertical field of view `fovy` (radians). The horizontal
+/// field follows from the aspect ratio of the image.
+///
+/// The image plane is where `t = 1`, so `t` measures distance in units of
+/// the focal length along the gaze.
+#[derive(PartialEq, Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PerspectiveCamera {
+    pub eye: Vector3,
+    pub basis: OrthonormalBasis,
+    pub nx: usize,
+    pub ny: usize,
+    pub fovy: f32,
+    pub focal_length: f32,
+}
+
+impl PerspectiveCamera {
+    pub fn new(eye: Vector3, gaze: &Vector3, up: &Vector3, nx: usize, ny: usize, fovy: f32, focal_length: f32) -> Self {
+        Self {
+            eye,
+            basis: OrthonormalBasis::from_two_vectors(&-gaze, up),
+            nx,
+            ny,
+            fovy,
+            focal_length,
+        }
+    }
+}
+
+impl Camera for PerspectiveCamera {
+    fn ray_through(&self, x: f32, y: f32) -> Ray {
+        let t = self.focal_length * (self.fovy / 2.).tan();
+        let r = t * self.nx as f32 / self.ny as f32;
+        let (u, v) = image_plane(x, y, self.nx, self.ny, r, t);
+        let OrthonormalBasis { u: bu, v: bv, w } = &self.basis;
+
+        Ray::new(self.eye, &(&(-self.focal_length * w) + &(u * bu)) + &(v * bv))
+    }
+}
+
+/// Image-plane coordinates `(u, v)` of the point `(x, y)` in pixel units,
+/// for the symmetric rectangle `[-r, r] x [-t, t]`.
+fn image_plane(x: f32, y: f32, nx: usize, ny: usize, r: f32, t: f32) -> (f32, f32) {
+    (-r + 2. * r * x / nx as f32, -t + 2. * t * y / ny as f32)
+}
+
+#[cfg(test)]
+mod test_camera {
+    use super::*;
+    use crate::math::vec3;
+    use crate::raytracing::{Sphere, Surface};
+    use std::f32::consts::FRAC_PI_2;
+
+    fn assert_near(a: &Vector3, b: &Vector3) {
+        assert!((a - b).norm() < 1e-5, "{:?} != {:?}", a, b);
+    }
+
+    #[test]
+    fn test_orthographic() {
+        let camera = OrthographicCamera::new(vec3(0., 0., 5.), &vec3(0., 0., -2.), &vec3(0., 1., 0.), 4, 2, 8., 4.);
+
+        // every ray runs along the gaze, from a grid on the viewport
+        for (i, j) in [(0, 0), (3, 1), (1, 0)] {
+            assert_near(&vec3(0., 0., -1.), &camera.ray(i, j).d);
+        }
+        assert_near(&vec3(-3., -1., 5.), &camera.ray(0, 0).e);
+        assert_near(&vec3(3., 1., 5.), &camera.ray(3, 1).e);
+        assert_near(&vec3(-4., -2., 5.), &camera.ray_with_offset(0, 0, &Vector2::new(0., 0.)).e);
+        assert_near(&vec3(0., 0., 5.), &camera.ray_through(2., 1.).e);
+    }
+
+    #[test]
+    fn test_perspective() {
+        let camera = PerspectiveCamera::new(vec3(1., 2., 3.), &vec3(1., 0., 0.), &vec3(0., 0., 1.), 200, 100, FRAC_PI_2, 2.);
+
+        // the center of the image is straight ahead, at the focal length
+        let center = camera.ray_through(100., 50.);
+        assert_eq!(vec3(1., 2., 3.), center.e);
+        assert_near(&vec3(2., 0., 0.), &center.d);
+
+        // the top edge is fovy / 2 above the gaze, z being up
+        let top = camera.ray_through(100., 100.).d.normalize();
+        assert_near(&vec3(1., 0., 1.).normalize(), &top);
+
+        // the sides are twice as far out for a 2:1 image, with u to the right
+        // of the gaze, here -y
+        assert_near(&vec3(2., -4., 0.), &camera.ray_through(200., 50.).d);
+        assert_near(&vec3(2., 4., -2.), &camera.ray_through(0., 0.).d);
+
+        // offsets move within the pixel
+        assert_eq!(camera.ray(10, 20), camera.ray_with_offset(10, 20, &Vector2::new(0.5, 0.5)));
+        assert_ne!(camera.ray(10, 20).d, camera.ray_with_offset(10, 20, &Vector2::new(0.1, 0.9)).d);
+    }
+
+    #[test]
+    fn test_viewpoint() {
+        // both cameras see a sphere they look at in the middle of the image
+        // and miss it in the corners
+        let sphere = Sphere::new(vec3(-3., 4., 2.), 1., 0);
+        let eye = vec3(5., -2., 6.);
+        let gaze = &sphere.center - &eye;
+        let up = vec3(0., 1., 0.);
+
+        let cameras: [Box<dyn Camera>; 2] = [
+            Box::new(OrthographicCamera::new(eye, &gaze, &up, 64, 48, 8., 6.)),
+            Box::new(PerspectiveCamera::new(eye, &gaze, &up, 64, 48, 0.8, 1.)),
+        ];
+        for camera in cameras {
+            let hit = sphere.hit(&camera.ray(32, 24), 0., f32::INFINITY).expect("hit");
+            assert!(hit.normal.dot(&gaze) < 0.);
+            assert!(sphere.hit(&camera.ray(0, 0), 0., f32::INFINITY).is_none());
+            assert!(sphere.hit(&camera.ray(63, 47), 0., f32::INFINITY).is_none());
+        }
+    }
+}
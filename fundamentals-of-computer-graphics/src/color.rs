@@ -0,0 +1,290 @@
+//! Linear RGB color with the sRGB primaries and D65 white point.
+//!
+//! Shading happens on linear values; encode to sRGB (after tone mapping
+//! anything above 1) only when writing pixels.
+
+use crate::math::{Matrix3, Vector3};
+use bytemuck::{Pod, Zeroable};
+use std::ops::{Add, Div, Mul, Sub};
+
+const RGB_TO_XYZ: [[f32; 3]; 3] = [
+    [0.4124564, 0.3575761, 0.1804375],
+    [0.2126729, 0.7151522, 0.0721750],
+    [0.0193339, 0.119192, 0.9503041],
+];
+
+const XYZ_TO_RGB: [[f32; 3]; 3] = [
+    [3.2404542, -1.5371385, -0.4985314],
+    [-0.969266, 1.8760108, 0.0415560],
+    [0.0556434, -0.2040259, 1.0572252],
+];
+
+#[repr(C)]
+#[derive(PartialEq, Debug, Copy, Clone, Default, Pod, Zeroable)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Color {
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
+}
+
+impl Color {
+    pub const BLACK: Self = Self::new(0., 0., 0.);
+    pub const WHITE: Self = Self::new(1., 1., 1.);
+
+    pub const fn new(r: f32, g: f32, b: f32) -> Self {
+        Self { r, g, b }
+    }
+
+    pub fn gray(v: f32) -> Self {
+        Self::new(v, v, v)
+    }
+
+    /// Decodes sRGB-encoded components in `[0, 1]`.
+    pub fn from_srgb(r: f32, g: f32, b: f32) -> Self {
+        Self::new(srgb_decode(r), srgb_decode(g), srgb_decode(b))
+    }
+
+    /// sRGB-encoded components, clamped to `[0, 1]`.
+    pub fn to_srgb(&self) -> [f32; 3] {
+        self.channels().map(|c| srgb_encode(c.clamp(0., 1.)))
+    }
+
+    pub fn from_srgb8([r, g, b]: [u8; 3]) -> Self {
+        Self::from_srgb(r as f32 / 255., g as f32 / 255., b as f32 / 255.)
+    }
+
+    /// 8-bit sRGB, ready to write to an image or canvas.
+    pub fn to_srgb8(&self) -> [u8; 3] {
+        self.to_srgb().map(|c| (c * 255.).round() as u8)
+    }
+
+    /// CIE 1931 XYZ, `y` being the luminance.
+    pub fn to_xyz(&self) -> Vector3 {
+        &Matrix3::from_rows(RGB_TO_XYZ) * &Vector3::new(self.r, self.g, self.b)
+    }
+
+    pub fn from_xyz(xyz: &Vector3) -> Self {
+        let rgb = &Matrix3::from_rows(XYZ_TO_RGB) * xyz;
+        Self::new(rgb.x, rgb.y, rgb.z)
+    }
+
+    /// Hue in degrees `[0, 360)`, saturation and value, computed on the
+    /// components as they are.
+    pub fn to_hsv(&self) -> [f32; 3] {
+        let max = self.r.max(self.g).max(self.b);
+        let min = self.r.min(self.g).min(self.b);
+        let delta = max - min;
+
+        let hue = if delta == 0. {
+            0.
+        } else if max == self.r {
+            60. * ((self.g - self.b) / delta).rem_euclid(6.)
+        } else if max == self.g {
+            60. * ((self.b - self.r) / delta + 2.)
+        } else {
+            60. * ((self.r - self.g) / delta + 4.)
+        };
+        let saturation = if max == 0. { 0. } else { delta / max };
+        [hue, saturation, max]
+    }
+
+    pub fn from_hsv(hue: f32, saturation: f32, value: f32) -> Self {
+        let h = hue.rem_euclid(360.) / 60.;
+        let c = value * saturation;
+        let x = c * (1. - (h.rem_euclid(2.) - 1.).abs());
+        let m = value - c;
+
+        let (r, g, b) = match h as u32 {
+            0 => (c, x, 0.),
+            1 => (x, c, 0.),
+            2 => (0., c, x),
+            3 => (0., x, c),
+            4 => (x, 0., c),
+            _ => (c, 0., x),
+        };
+        Self::new(r + m, g + m, b + m)
+    }
+
+    /// Relative luminance, Rec. 709 weights.
+    pub fn luminance(&self) -> f32 {
+        0.2126 * self.r + 0.7152 * self.g + 0.0722 * self.b
+    }
+
+    /// Scales by `2^stops`.
+    pub fn exposure(&self, stops: f32) -> Self {
+        self * stops.exp2()
+    }
+
+    /// Reinhard et al. 2002 global operator, `L / (1 + L)` on the luminance
+    /// so hues are kept.
+    pub fn reinhard(&self) -> Self {
+        let l = self.luminance();
+        if l <= 0. { Self::BLACK } else { self * (1. / (1. + l)) }
+    }
+
+    /// Narkowicz's fit of the ACES filmic curve, per channel, mapping to
+    /// `[0, 1]`.
+    pub fn aces(&self) -> Self {
+        let f = |x: f32| ((x * (2.51 * x + 0.03)) / (x * (2.43 * x + 0.59) + 0.14)).clamp(0., 1.);
+        Self::new(f(self.r), f(self.g), f(self.b))
+    }
+
+    pub fn channels(&self) -> [f32; 3] {
+        [self.r, self.g, self.b]
+    }
+}
+
+/// The sRGB transfer function, linear to encoded.
+pub fn srgb_encode(c: f32) -> f32 {
+    if c <= 0.0031308 { 12.92 * c } else { 1.055 * c.powf(1. / 2.4) - 0.055 }
+}
+
+/// The inverse sRGB transfer function, encoded to linear.
+pub fn srgb_decode(c: f32) -> f32 {
+    if c <= 0.04045 { c / 12.92 } else { ((c + 0.055) / 1.055).powf(2.4) }
+}
+
+impl Add<&Color> for &Color {
+    type Output = Color;
+
+    fn add(self, rhs: &Color) -> Self::Output {
+        Color::new(self.r + rhs.r, self.g + rhs.g, self.b + rhs.b)
+    }
+}
+
+impl Sub<&Color> for &Color {
+    type Output = Color;
+
+    fn sub(self, rhs: &Color) -> Self::Output {
+        Color::new(self.r - rhs.r, self.g - rhs.g, self.b - rhs.b)
+    }
+}
+
+/// Component-wise, e.g. light times reflectance.
+impl Mul<&Color> for &Color {
+    type Output = Color;
+
+    fn mul(self, rhs: &Color) -> Self::Output {
+        Color::new(self.r * rhs.r, self.g * rhs.g, self.b * rhs.b)
+    }
+}
+
+impl Mul<f32> for &Color {
+    type Output = Color;
+
+    fn mul(self, rhs: f32) -> Self::Output {
+        Color::new(self.r * rhs, self.g * rhs, self.b * rhs)
+    }
+}
+
+impl Mul<&Color> for f32 {
+    type Output = Color;
+
+    fn mul(self, rhs: &Color) -> Self::Output {
+        rhs * self
+    }
+}
+
+impl Div<f32> for &Color {
+    type Output = Color;
+
+    fn div(self, rhs: f32) -> Self::Output {
+        Color::new(self.r / rhs, self.g / rhs, self.b / rhs)
+    }
+}
+
+#[cfg(test)]
+mod test_color {
+    use super::*;
+
+    fn assert_near(a: &Color, b: &Color) {
+        assert!((a - b).channels().iter().all(|c| c.abs() < 1e-4), "{:?} != {:?}", a, b);
+    }
+
+    #[test]
+    fn test_srgb() {
+        assert_eq!(0., srgb_encode(0.));
+        assert!((srgb_encode(1.) - 1.).abs() < 1e-6);
+        assert!((srgb_encode(0.2140) - 0.5).abs() < 1e-3);
+        for i in 0..=255u8 {
+            assert_eq!([i, i, i], Color::from_srgb8([i, i, i]).to_srgb8());
+        }
+
+        assert_eq!([255, 255, 255], Color::gray(4.).to_srgb8());
+        assert_eq!([0, 0, 0], Color::gray(-1.).to_srgb8());
+    }
+
+    #[test]
+    fn test_xyz() {
+        // D65 white
+        let white = Color::WHITE.to_xyz();
+        assert!((&white - &Vector3::new(0.95047, 1., 1.08883)).norm() < 1e-4);
+
+        let c = Color::new(0.2, 0.5, 0.9);
+        assert_near(&c, &Color::from_xyz(&c.to_xyz()));
+        assert!((c.to_xyz().y - c.luminance()).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_hsv() {
+        assert_eq!([0., 1., 1.], Color::new(1., 0., 0.).to_hsv());
+        assert_eq!([120., 1., 1.], Color::new(0., 1., 0.).to_hsv());
+        assert_eq!([240., 0.5, 0.5], Color::new(0.25, 0.25, 0.5).to_hsv());
+        assert_eq!([0., 0., 0.3], Color::gray(0.3).to_hsv());
+
+        for c in [Color::new(0.9, 0.2, 0.4), Color::new(0.1, 0.7, 0.3), Color::new(0.5, 0.4, 0.8)] {
+            let [h, s, v] = c.to_hsv();
+            assert_near(&c, &Color::from_hsv(h, s, v));
+        }
+        assert_near(&Color::new(1., 0., 0.), &Color::from_hsv(360., 1., 1.));
+    }
+
+    #[test]
+    fn test_tone_mapping() {
+        let c = Color::new(0.5, 1., 2.);
+
+        assert_eq!(Color::new(2., 4., 8.), c.exposure(2.));
+        assert_eq!(Color::new(0.25, 0.5, 1.), c.exposure(-1.));
+
+        // Reinhard keeps ratios and compresses luminance below 1.
+        let r = Color::gray(1000.).reinhard();
+        assert!(r.luminance() < 1.);
+        let r = c.reinhard();
+        assert!((r.b / r.r - 4.).abs() < 1e-5);
+        assert_eq!(Color::BLACK, Color::BLACK.reinhard());
+
+        // ACES is monotonic and maps to [0, 1].
+        assert_eq!(Color::BLACK, Color::BLACK.aces());
+        let mut previous = 0.;
+        for i in 0..100 {
+            let v = Color::gray(i as f32 * 0.5).aces().r;
+            assert!((previous..=1.).contains(&v));
+            previous = v;
+        }
+    }
+
+    #[test]
+    fn test_ops() {
+        let (a, b) = (Color::new(0.5, 1., 2.), Color::new(2., 0.5, 0.25));
+
+        assert_eq!(Color::new(2.5, 1.5, 2.25), &a + &b);
+        assert_eq!(Color::new(1., 0.5, 0.5), &a * &b);
+        assert_eq!(Color::new(1., 2., 4.), 2. * &a);
+        assert_eq!(Color::new(0.25, 0.5, 1.), &a / 2.);
+    }
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod test_serde {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let c = Color::new(0.25, 0.5, 1.);
+        let json = serde_json::to_string(&c).unwrap();
+
+        assert_eq!(r#"{"r":0.25,"g":0.5,"b":1.0}"#, json);
+        assert_eq!(c, serde_json::from_str(&json).unwrap());
+    }
+}
@@ -0,0 +1,450 @@
+//! Piecewise cubic curves (FCG chapter 15).
+//!
+//! Every cubic segment is converted to its Bézier control points, so
+//! evaluation, subdivision and tessellation are written once on
+//! `CubicBezier`. Curves are parameterized by `t` in `[0, 1]` over all of
+//! their segments; tessellation outputs `Vector3` polylines that can be drawn
+//! as a line strip.
+
+use crate::math::Vector3;
+
+/// Deepest subdivision during tessellation, `2^16` lines per segment.
+const MAX_DEPTH: u32 = 16;
+
+pub trait Curve {
+    fn segment_count(&self) -> usize;
+
+    /// Segment `i` as Bézier control points.
+    fn segment(&self, i: usize) -> CubicBezier;
+
+    fn point(&self, t: f32) -> Vector3 {
+        let (i, s) = self.locate(t);
+        self.segment(i).point(s)
+    }
+
+    /// First derivative with respect to `t`.
+    fn derivative(&self, t: f32) -> Vector3 {
+        let (i, s) = self.locate(t);
+        self.segment_count() as f32 * &self.segment(i).derivative(s)
+    }
+
+    /// Second derivative with respect to `t`.
+    fn second_derivative(&self, t: f32) -> Vector3 {
+        let (i, s) = self.locate(t);
+        let n = self.segment_count() as f32;
+        (n * n) * &self.segment(i).second_derivative(s)
+    }
+
+    /// The segment containing `t` and the parameter within it. Panics if
+    /// there are no segments, e.g. for a spline with fewer than 4 points;
+    /// `tessellate` and `arc_length` return empty results instead.
+    fn locate(&self, t: f32) -> (usize, f32) {
+        let n = self.segment_count();
+        assert!(n > 0, "curve has no segments");
+
+        let x = t.clamp(0., 1.) * n as f32;
+        let i = (x.floor() as usize).min(n - 1);
+        (i, x - i as f32)
+    }
+
+    /// Polyline through the curve whose lines stay within `tolerance` of it.
+    /// Flat parts get few points and tight bends many.
+    fn tessellate(&self, tolerance: f32) -> Vec<Vector3> {
+        if self.segment_count() == 0 {
+            return vec![];
+        }
+
+        let mut points = vec![self.segment(0).p[0]];
+        for i in 0..self.segment_count() {
+            self.segment(i).flatten(tolerance, MAX_DEPTH, &mut points);
+        }
+        points
+    }
+
+    /// Arc-length table from `samples` chords per segment.
+    fn arc_length(&self, samples: usize) -> ArcLength {
+        if self.segment_count() == 0 {
+            return ArcLength { params: vec![], lengths: vec![] };
+        }
+
+        let n = self.segment_count() * samples.max(1);
+        let mut params = Vec::with_capacity(n + 1);
+        let mut lengths = Vec::with_capacity(n + 1);
+
+        let mut previous = self.point(0.);
+        let mut length = 0.;
+        params.push(0.);
+        lengths.push(0.);
+        for k in 1..=n {
+            let t = k as f32 / n as f32;
+            let p = self.point(t);
+            length += (&p - &previous).norm();
+            previous = p;
+            params.push(t);
+            lengths.push(length);
+        }
+
+        ArcLength { params, lengths }
+    }
+}
+
+/// Cubic Bézier segment with control points `p`.
+#[derive(PartialEq, Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CubicBezier {
+    pub p: [Vector3; 4],
+}
+
+impl CubicBezier {
+    pub fn new(p0: Vector3, p1: Vector3, p2: Vector3, p3: Vector3) -> Self {
+        Self { p: [p0, p1, p2, p3] }
+    }
+
+    /// Hermite segment from `p0` to `p1` with end tangents `v0` and `v1`.
+    pub fn from_hermite(p0: &Vector3, p1: &Vector3, v0: &Vector3, v1: &Vector3) -> Self {
+        Self::new(*p0, p0 + &(v0 / 3.), p1 - &(v1 / 3.), *p1)
+    }
+
+    pub fn point(&self, t: f32) -> Vector3 {
+        let [p0, p1, p2, p3] = &self.p;
+        let s = 1. - t;
+
+        let a = &((s * s * s) * p0) + &((3. * s * s * t) * p1);
+        let b = &((3. * s * t * t) * p2) + &((t * t * t) * p3);
+        &a + &b
+    }
+
+    pub fn derivative(&self, t: f32) -> Vector3 {
+        let [p0, p1, p2, p3] = &self.p;
+        let s = 1. - t;
+
+        let a = &((s * s) * &(p1 - p0)) + &((2. * s * t) * &(p2 - p1));
+        3. * &(&a + &((t * t) * &(p3 - p2)))
+    }
+
+    pub fn second_derivative(&self, t: f32) -> Vector3 {
+        let [p0, p1, p2, p3] = &self.p;
+
+        let a = &(&(p2 - p1) - &(p1 - p0)) * (1. - t);
+        let b = &(&(p3 - p2) - &(p2 - p1)) * t;
+        6. * &(&a + &b)
+    }
+
+    /// Splits the segment at `t` with de Casteljau's algorithm. The halves
+    /// trace exactly the same curve.
+    pub fn subdivide(&self, t: f32) -> (Self, Self) {
+        let [p0, p1, p2, p3] = &self.p;
+
+        let (q0, q1, q2) = (lerp(p0, p1, t), lerp(p1, p2, t), lerp(p2, p3, t));
+        let (r0, r1) = (lerp(&q0, &q1, t), lerp(&q1, &q2, t));
+        let s = lerp(&r0, &r1, t);
+
+        (Self::new(*p0, q0, r0, s), Self::new(s, r1, q2, *p3))
+    }
+
+    /// Largest distance of the inner control points from the chord. The
+    /// curve lies in their convex hull, so it is at most this far from the
+    /// chord too.
+    pub fn flatness(&self) -> f32 {
+        let [p0, p1, p2, p3] = &self.p;
+        let chord = p3 - p0;
+        let length = chord.norm();
+
+        let distance = |p: &Vector3| {
+            if length == 0. {
+                (p - p0).norm()
+            } else {
+                (p - p0).cross(&chord).norm() / length
+            }
+        };
+        distance(p1).max(distance(p2))
+    }
+
+    /// Appends the end points of the lines approximating the segment, not
+    /// including its start.
+    fn flatten(&self, tolerance: f32, depth: u32, points: &mut Vec<Vector3>) {
+        if depth == 0 || self.flatness() <= tolerance {
+            points.push(self.p[3]);
+        } else {
+            let (left, right) = self.subdivide(0.5);
+            left.flatten(tolerance, depth - 1, points);
+            right.flatten(tolerance, depth - 1, points);
+        }
+    }
+}
+
+impl Curve for CubicBezier {
+    fn segment_count(&self) -> usize {
+        1
+    }
+
+    fn segment(&self, _: usize) -> CubicBezier {
+        *self
+    }
+}
+
+/// Interpolates `points` with the given tangent at each point.
+#[derive(PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Hermite {
+    pub points: Vec<Vector3>,
+    pub tangents: Vec<Vector3>,
+}
+
+impl Hermite {
+    pub fn new(points: Vec<Vector3>, tangents: Vec<Vector3>) -> Self {
+        assert_eq!(points.len(), tangents.len(), "one tangent per point");
+        Self { points, tangents }
+    }
+}
+
+impl Curve for Hermite {
+    fn segment_count(&self) -> usize {
+        self.points.len().saturating_sub(1)
+    }
+
+    fn segment(&self, i: usize) -> CubicBezier {
+        let (p, v) = (&self.points, &self.tangents);
+        CubicBezier::from_hermite(&p[i], &p[i + 1], &v[i], &v[i + 1])
+    }
+}
+
+/// Interpolates all but the first and last point, which only set the end
+/// tangents. The tangent at each point is half the difference of its
+/// neighbours.
+#[derive(PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CatmullRom {
+    pub points: Vec<Vector3>,
+}
+
+impl CatmullRom {
+    pub fn new(points: Vec<Vector3>) -> Self {
+        Self { points }
+    }
+}
+
+impl Curve for CatmullRom {
+    fn segment_count(&self) -> usize {
+        self.points.len().saturating_sub(3)
+    }
+
+    fn segment(&self, i: usize) -> CubicBezier {
+        let [p0, p1, p2, p3] = [0, 1, 2, 3].map(|k| &self.points[i + k]);
+        CubicBezier::new(*p1, p1 + &(&(p2 - p0) / 6.), p2 - &(&(p3 - p1) / 6.), *p2)
+    }
+}
+
+/// Uniform cubic B-spline. It approximates its control points and is C2
+/// continuous.
+#[derive(PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BSpline {
+    pub control: Vec<Vector3>,
+}
+
+impl BSpline {
+    pub fn new(control: Vec<Vector3>) -> Self {
+        Self { control }
+    }
+}
+
+impl Curve for BSpline {
+    fn segment_count(&self) -> usize {
+        self.control.len().saturating_sub(3)
+    }
+
+    fn segment(&self, i: usize) -> CubicBezier {
+        let [p0, p1, p2, p3] = [0, 1, 2, 3].map(|k| &self.control[i + k]);
+        CubicBezier::new(
+            &(&(p0 + &(4. * p1)) + p2) / 6.,
+            &(&(2. * p1) + p2) / 3.,
+            &(p1 + &(2. * p2)) / 3.,
+            &(&(p1 + &(4. * p2)) + p3) / 6.,
+        )
+    }
+}
+
+/// Cumulative chord lengths at increasing parameters, for moving along a
+/// curve at constant speed. Empty for a curve without segments, with a
+/// total of 0.
+#[derive(PartialEq, Debug, Clone)]
+pub struct ArcLength {
+    params: Vec<f32>,
+    lengths: Vec<f32>,
+}
+
+impl ArcLength {
+    pub fn total(&self) -> f32 {
+        self.lengths.last().copied().unwrap_or(0.)
+    }
+
+    /// Parameter at distance `s` along the curve, clamped to its ends.
+    pub fn parameter(&self, s: f32) -> f32 {
+        if self.lengths.is_empty() {
+            return 0.;
+        }
+
+        let s = s.clamp(0., self.total());
+        let k = self.lengths.partition_point(|&l| l < s).max(1);
+
+        let (l0, l1) = (self.lengths[k - 1], self.lengths[k]);
+        let (t0, t1) = (self.params[k - 1], self.params[k]);
+        if l1 == l0 { t0 } else { t0 + (t1 - t0) * (s - l0) / (l1 - l0) }
+    }
+}
+
+fn lerp(a: &Vector3, b: &Vector3, t: f32) -> Vector3 {
+    a + &(t * &(b - a))
+}
+
+#[cfg(test)]
+mod test_curves {
+    use super::*;
+    use crate::math::vec3;
+
+    fn assert_near(a: &Vector3, b: &Vector3) {
+        assert!((a - b).norm() < 1e-4, "{:?} != {:?}", a, b);
+    }
+
+    fn arc() -> CubicBezier {
+        // Quarter of the unit circle in the xy plane.
+        let k = 0.5523;
+        CubicBezier::new(vec3(1., 0., 0.), vec3(1., k, 0.), vec3(k, 1., 0.), vec3(0., 1., 0.))
+    }
+
+    #[test]
+    fn test_bezier() {
+        let c = arc();
+
+        assert_eq!(c.p[0], c.point(0.));
+        assert_eq!(c.p[3], c.point(1.));
+        assert_near(&(3. * &(&c.p[1] - &c.p[0])), &c.derivative(0.));
+        assert_near(&(3. * &(&c.p[3] - &c.p[2])), &c.derivative(1.));
+
+        let h = 1e-2;
+        for t in [0.1, 0.5, 0.8] {
+            let d = &(&c.point(t + h) - &c.point(t - h)) / (2. * h);
+            assert!((&d - &c.derivative(t)).norm() < 1e-3);
+            let dd = &(&c.derivative(t + h) - &c.derivative(t - h)) / (2. * h);
+            assert!((&dd - &c.second_derivative(t)).norm() < 1e-2);
+            assert!((c.point(t).norm() - 1.).abs() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn test_subdivide() {
+        let c = arc();
+        let (left, right) = c.subdivide(0.3);
+
+        assert_eq!(left.p[3], right.p[0]);
+        for s in [0., 0.25, 0.5, 1.] {
+            assert_near(&c.point(0.3 * s), &left.point(s));
+            assert_near(&c.point(0.3 + 0.7 * s), &right.point(s));
+        }
+    }
+
+    #[test]
+    fn test_hermite() {
+        let points = vec![vec3(0., 0., 0.), vec3(1., 1., 0.), vec3(2., 0., 1.)];
+        let tangents = vec![vec3(1., 0., 0.), vec3(1., 0., 0.), vec3(0., 0., 3.)];
+        let c = Hermite::new(points.clone(), tangents.clone());
+
+        assert_eq!(2, c.segment_count());
+        assert_eq!(points[0], c.point(0.));
+        assert_near(&points[1], &c.point(0.5));
+        assert_near(&points[2], &c.point(1.));
+
+        // Per-segment tangents, scaled by the two segments of the curve.
+        assert_near(&tangents[0], &c.segment(0).derivative(0.));
+        assert_near(&(2. * &tangents[1]), &c.derivative(0.5));
+        assert_near(&tangents[2], &c.segment(1).derivative(1.));
+    }
+
+    #[test]
+    fn test_catmull_rom() {
+        let points = vec![vec3(0., 0., 0.), vec3(1., 2., 0.), vec3(3., 3., 0.), vec3(4., 1., 1.), vec3(6., 0., 0.)];
+        let c = CatmullRom::new(points.clone());
+
+        assert_eq!(2, c.segment_count());
+        assert_near(&points[1], &c.point(0.));
+        assert_near(&points[2], &c.point(0.5));
+        assert_near(&points[3], &c.point(1.));
+        assert_near(&(&points[3] - &points[1]), &c.derivative(0.5));
+    }
+
+    #[test]
+    fn test_bspline() {
+        // Evenly spaced collinear control points give a line traced at
+        // constant speed.
+        let line = BSpline::new((0..6).map(|i| vec3(i as f32, 0., 0.)).collect());
+        assert_eq!(3, line.segment_count());
+        assert_near(&vec3(1., 0., 0.), &line.point(0.));
+        assert_near(&vec3(2.5, 0., 0.), &line.point(0.5));
+        assert_near(&vec3(3., 0., 0.), &line.derivative(0.3));
+
+        // C2 continuous where the segments join.
+        let c = BSpline::new(vec![vec3(0., 0., 0.), vec3(1., 3., 0.), vec3(2., -1., 1.), vec3(4., 2., 0.), vec3(5., 0., 2.)]);
+        let (a, b) = (c.segment(0), c.segment(1));
+        assert_near(&a.point(1.), &b.point(0.));
+        assert_near(&a.derivative(1.), &b.derivative(0.));
+        assert_near(&a.second_derivative(1.), &b.second_derivative(0.));
+    }
+
+    #[test]
+    fn test_arc_length() {
+        // A straight segment with uneven parameter speed.
+        let c = CubicBezier::new(vec3(0., 0., 0.), vec3(0.1, 0., 0.), vec3(0.2, 0., 0.), vec3(3., 0., 0.));
+        let table = c.arc_length(256);
+
+        assert!((table.total() - 3.).abs() < 1e-4);
+        assert_eq!(0., table.parameter(-1.));
+        assert_eq!(1., table.parameter(10.));
+        for s in [0.5, 1., 2.25] {
+            assert!((c.point(table.parameter(s)).x - s).abs() < 1e-2);
+        }
+
+        assert!((arc().arc_length(64).total() - std::f32::consts::FRAC_PI_2).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_tessellate() {
+        let line = CubicBezier::new(vec3(0., 0., 0.), vec3(1., 1., 1.), vec3(2., 2., 2.), vec3(3., 3., 3.));
+        assert_eq!(vec![line.p[0], line.p[3]], line.tessellate(1e-3));
+
+        let c = arc();
+        let coarse = c.tessellate(1e-2);
+        let fine = c.tessellate(1e-4);
+        assert!(coarse.len() > 2 && fine.len() > coarse.len());
+        assert_eq!((c.p[0], c.p[3]), (fine[0], *fine.last().unwrap()));
+
+        // Every chord midpoint is close to the circle.
+        for w in fine.windows(2) {
+            let m = &(&w[0] + &w[1]) / 2.;
+            assert!((m.norm() - 1.).abs() < 1e-3);
+        }
+
+        let spline = CatmullRom::new(vec![vec3(0., 0., 0.), vec3(1., 2., 0.), vec3(3., 3., 0.), vec3(4., 1., 1.)]);
+        let points = spline.tessellate(1e-3);
+        assert_eq!((spline.point(0.), spline.point(1.)), (points[0], *points.last().unwrap()));
+    }
+
+    #[test]
+    fn test_no_segments() {
+        // valid to construct, but too few points for a single segment
+        let curves: [Box<dyn Curve>; 4] = [
+            Box::new(CatmullRom::new(vec![vec3(0., 0., 0.), vec3(1., 0., 0.), vec3(2., 1., 0.)])),
+            Box::new(BSpline::new(vec![])),
+            Box::new(Hermite::new(vec![vec3(0., 0., 0.)], vec![vec3(1., 0., 0.)])),
+            Box::new(Hermite::new(vec![], vec![])),
+        ];
+        for c in curves {
+            assert_eq!(0, c.segment_count());
+            assert!(c.tessellate(1e-3).is_empty());
+
+            let table = c.arc_length(16);
+            assert_eq!(0., table.total());
+            assert_eq!(0., table.parameter(1.));
+        }
+    }
+}
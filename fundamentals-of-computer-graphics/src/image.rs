@@ -0,0 +1,409 @@
+//! Floating-point images with separable filtering and resampling, following
+//! FCG chapter 9.
+//!
+//! Pixels are linear RGBA with premultiplied alpha, so filtering never
+//! bleeds the color of transparent pixels into their neighbours. Conversion
+//! from and to 8-bit sRGB happens at the edges, in `from_rgba8` and
+//! `to_rgba8`.
+
+use crate::color::{srgb_decode, srgb_encode};
+use std::f32::consts::PI;
+
+/// A 1D reconstruction filter, used along rows and columns in turn.
+/// `evaluate` integrates to 1 and is zero beyond `radius`.
+pub trait Filter {
+    fn radius(&self) -> f32;
+    fn evaluate(&self, x: f32) -> f32;
+}
+
+/// Constant over `[-radius, radius)`.
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub struct BoxFilter {
+    pub radius: f32,
+}
+
+/// Linear falloff to zero at `radius`.
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub struct Tent {
+    pub radius: f32,
+}
+
+/// Truncated at three standard deviations. A `sigma` below `MIN_SIGMA`,
+/// zero included, is taken as `MIN_SIGMA`, which samples at a single point.
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub struct Gaussian {
+    pub sigma: f32,
+}
+
+impl Gaussian {
+    pub const MIN_SIGMA: f32 = 1e-3;
+
+    fn sigma(&self) -> f32 {
+        self.sigma.max(Self::MIN_SIGMA)
+    }
+}
+
+/// The cubic family of Mitchell and Netravali (1988), of radius 2. `b = 1,
+/// c = 0` is the cubic B-spline, `b = 0, c = 0.5` Catmull-Rom.
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub struct MitchellNetravali {
+    pub b: f32,
+    pub c: f32,
+}
+
+/// `b = c = 1/3`, the compromise recommended in the paper.
+impl Default for MitchellNetravali {
+    fn default() -> Self {
+        Self { b: 1. / 3., c: 1. / 3. }
+    }
+}
+
+impl Filter for BoxFilter {
+    fn radius(&self) -> f32 {
+        self.radius
+    }
+
+    fn evaluate(&self, x: f32) -> f32 {
+        if -self.radius <= x && x < self.radius { 0.5 / self.radius } else { 0. }
+    }
+}
+
+impl Filter for Tent {
+    fn radius(&self) -> f32 {
+        self.radius
+    }
+
+    fn evaluate(&self, x: f32) -> f32 {
+        (1. - x.abs() / self.radius).max(0.) / self.radius
+    }
+}
+
+impl Filter for Gaussian {
+    fn radius(&self) -> f32 {
+        3. * self.sigma()
+    }
+
+    fn evaluate(&self, x: f32) -> f32 {
+        if x.abs() > self.radius() {
+            return 0.;
+        }
+        let sigma = self.sigma();
+        (-x * x / (2. * sigma * sigma)).exp() / (sigma * (2. * PI).sqrt())
+    }
+}
+
+impl Filter for MitchellNetravali {
+    fn radius(&self) -> f32 {
+        2.
+    }
+
+    fn evaluate(&self, x: f32) -> f32 {
+        let (b, c) = (self.b, self.c);
+        let x = x.abs();
+        let value = if x < 1. {
+            (12. - 9. * b - 6. * c) * x * x * x + (-18. + 12. * b + 6. * c) * x * x + (6. - 2. * b)
+        } else if x < 2. {
+            (-b - 6. * c) * x * x * x + (6. * b + 30. * c) * x * x + (-12. * b - 48. * c) * x + (8. * b + 24. * c)
+        } else {
+            0.
+        };
+        value / 6.
+    }
+}
+
+/// RGBA image, rows top to bottom like `Framebuffer::as_bytes`.
+#[derive(PartialEq, Debug, Clone)]
+pub struct Image {
+    width: usize,
+    height: usize,
+    pixels: Vec<[f32; 4]>,
+}
+
+impl Image {
+    /// Transparent black.
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![[0.; 4]; width * height],
+        }
+    }
+
+    pub fn from_fn(width: usize, height: usize, mut f: impl FnMut(usize, usize) -> [f32; 4]) -> Self {
+        let pixels = (0..width * height).map(|i| f(i % width, i / width)).collect();
+        Self { width, height, pixels }
+    }
+
+    /// Decodes straight-alpha 8-bit sRGB, e.g. canvas `ImageData`.
+    pub fn from_rgba8(width: usize, height: usize, bytes: &[u8]) -> Self {
+        assert_eq!(width * height * 4, bytes.len(), "expected {width}x{height} RGBA pixels");
+
+        let pixels = bytes
+            .chunks_exact(4)
+            .map(|p| {
+                let a = p[3] as f32 / 255.;
+                let [r, g, b] = [p[0], p[1], p[2]].map(|c| srgb_decode(c as f32 / 255.) * a);
+                [r, g, b, a]
+            })
+            .collect();
+        Self { width, height, pixels }
+    }
+
+    /// Encodes to straight-alpha 8-bit sRGB, clamping to `[0, 1]`.
+    pub fn to_rgba8(&self) -> Vec<u8> {
+        let byte = |c: f32| (c.clamp(0., 1.) * 255.).round() as u8;
+        self.pixels
+            .iter()
+            .flat_map(|&[r, g, b, a]| {
+                let [r, g, b] = if a > 0. { [r, g, b].map(|c| byte(srgb_encode(c / a))) } else { [0; 3] };
+                [r, g, b, byte(a)]
+            })
+            .collect()
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn pixel(&self, x: usize, y: usize) -> [f32; 4] {
+        self.pixels[y * self.width + x]
+    }
+
+    pub fn set_pixel(&mut self, x: usize, y: usize, rgba: [f32; 4]) {
+        self.pixels[y * self.width + x] = rgba;
+    }
+
+    /// Bilinear lookup at texture coordinates `(u, v)` in `[0, 1]^2`, `(0, 0)`
+    /// being the top-left corner of the image. Clamps at the borders; an
+    /// empty image is transparent black everywhere.
+    pub fn sample(&self, u: f32, v: f32) -> [f32; 4] {
+        if self.pixels.is_empty() {
+            return [0.; 4];
+        }
+
+        let x = (u * self.width as f32 - 0.5).clamp(0., (self.width - 1) as f32);
+        let y = (v * self.height as f32 - 0.5).clamp(0., (self.height - 1) as f32);
+        let (x0, y0) = (x.floor() as usize, y.floor() as usize);
+        let (x1, y1) = ((x0 + 1).min(self.width - 1), (y0 + 1).min(self.height - 1));
+        let (fx, fy) = (x - x0 as f32, y - y0 as f32);
+
+        let lerp = |a: [f32; 4], b: [f32; 4], t: f32| std::array::from_fn(|i| a[i] + t * (b[i] - a[i]));
+        let top = lerp(self.pixel(x0, y0), self.pixel(x1, y0), fx);
+        let bottom = lerp(self.pixel(x0, y1), self.pixel(x1, y1), fx);
+        lerp(top, bottom, fy)
+    }
+
+    /// Discrete convolution with `filter` sampled at integer offsets, rows
+    /// then columns. Borders are extended by clamping.
+    pub fn convolve(&self, filter: &dyn Filter) -> Self {
+        self.resample(self.width, self.height, filter)
+    }
+
+    /// Resamples to `width` by `height` (FCG 9.4). When shrinking, the filter
+    /// is widened by the scale factor so it also removes the frequencies the
+    /// smaller image cannot hold.
+    pub fn resample(&self, width: usize, height: usize, filter: &dyn Filter) -> Self {
+        let rows = weights(self.width, width, filter);
+        let horizontal = Self::from_fn(width, self.height, |x, y| {
+            accumulate(rows[x].iter().map(|&(i, w)| (self.pixel(i, y), w)))
+        });
+
+        let columns = weights(self.height, height, filter);
+        Self::from_fn(width, height, |x, y| {
+            accumulate(columns[y].iter().map(|&(i, w)| (horizontal.pixel(x, i), w)))
+        })
+    }
+
+    /// Halves both dimensions, rounding down but never below 1.
+    pub fn downsample(&self, filter: &dyn Filter) -> Self {
+        self.resample((self.width / 2).max(1), (self.height / 2).max(1), filter)
+    }
+
+    /// This image followed by successively halved levels down to 1x1.
+    pub fn mip_chain(&self, filter: &dyn Filter) -> Vec<Self> {
+        let mut levels = vec![self.clone()];
+        while let Some(last) = levels.last().filter(|l| l.width > 1 || l.height > 1) {
+            let next = last.downsample(filter);
+            levels.push(next);
+        }
+        levels
+    }
+}
+
+/// For each of `out_len` output samples, the input indices it reads and
+/// their normalized weights. Output sample `o` is centered on input
+/// coordinate `(o + 1/2) in_len / out_len - 1/2`. Without input samples
+/// there are no taps, so the output is transparent black.
+fn weights(in_len: usize, out_len: usize, filter: &dyn Filter) -> Vec<Vec<(usize, f32)>> {
+    if in_len == 0 {
+        return vec![vec![]; out_len];
+    }
+
+    let ratio = in_len as f32 / out_len as f32;
+    let scale = ratio.max(1.);
+    let radius = filter.radius() * scale;
+
+    (0..out_len)
+        .map(|o| {
+            let center = (o as f32 + 0.5) * ratio - 0.5;
+            let (lo, hi) = ((center - radius).ceil() as i64, (center + radius).floor() as i64);
+
+            let mut taps: Vec<(usize, f32)> = (lo..=hi)
+                .map(|i| (i.clamp(0, in_len as i64 - 1) as usize, filter.evaluate((i as f32 - center) / scale)))
+                .filter(|&(_, w)| w != 0.)
+                .collect();
+
+            // too narrow to reach any sample, fall back to the nearest one
+            let total: f32 = taps.iter().map(|&(_, w)| w).sum();
+            if total == 0. {
+                return vec![((center.round().max(0.) as usize).min(in_len - 1), 1.)];
+            }
+
+            taps.iter_mut().for_each(|(_, w)| *w /= total);
+            taps
+        })
+        .collect()
+}
+
+fn accumulate(taps: impl Iterator<Item = ([f32; 4], f32)>) -> [f32; 4] {
+    taps.fold([0.; 4], |sum, (p, w)| std::array::from_fn(|i| sum[i] + w * p[i]))
+}
+
+#[cfg(test)]
+mod test_image {
+    use super::*;
+
+    fn assert_near(a: [f32; 4], b: [f32; 4]) {
+        assert!((0..4).all(|i| (a[i] - b[i]).abs() < 1e-5), "{:?} != {:?}", a, b);
+    }
+
+    #[test]
+    fn test_filters() {
+        let filters: [&dyn Filter; 6] = [
+            &BoxFilter { radius: 0.5 },
+            &Tent { radius: 1.5 },
+            &Gaussian { sigma: 0.7 },
+            &MitchellNetravali::default(),
+            &MitchellNetravali { b: 1., c: 0. },
+            &MitchellNetravali { b: 0., c: 0.5 },
+        ];
+
+        for filter in filters {
+            let n = 10000;
+            let dx = 2. * filter.radius() / n as f32;
+            let integral: f32 = (0..n).map(|i| filter.evaluate(-filter.radius() + (i as f32 + 0.5) * dx) * dx).sum();
+            assert!((integral - 1.).abs() < 5e-3, "{integral}");
+            assert_eq!(0., filter.evaluate(filter.radius() + 0.01));
+        }
+
+        // Catmull-Rom interpolates: 1 at 0 and 0 at the other integers
+        let catmull_rom = MitchellNetravali { b: 0., c: 0.5 };
+        assert_eq!(1., catmull_rom.evaluate(0.));
+        assert_eq!(0., catmull_rom.evaluate(1.));
+
+        // a Gaussian without width still has finite weights
+        let point = Gaussian { sigma: 0. };
+        assert!(point.radius() > 0. && point.evaluate(0.).is_finite());
+        assert_eq!(0., point.evaluate(0.5));
+    }
+
+    #[test]
+    fn test_convolve() {
+        // a constant image is unchanged, borders included
+        let flat = Image::from_fn(7, 5, |_, _| [0.2, 0.4, 0.6, 1.]);
+        for filter in [&Gaussian { sigma: 1. } as &dyn Filter, &Tent { radius: 2. }] {
+            let blurred = flat.convolve(filter);
+            for y in 0..5 {
+                for x in 0..7 {
+                    assert_near([0.2, 0.4, 0.6, 1.], blurred.pixel(x, y));
+                }
+            }
+        }
+
+        // a tent of radius 2 spreads a single pixel as 1/4, 1/2, 1/4 per axis
+        let mut dot = Image::new(5, 5);
+        dot.set_pixel(2, 2, [1.; 4]);
+        let blurred = dot.convolve(&Tent { radius: 2. });
+        assert_near([0.25; 4], blurred.pixel(2, 2));
+        assert_near([0.125; 4], blurred.pixel(1, 2));
+        assert_near([0.0625; 4], blurred.pixel(3, 1));
+        assert_near([0.; 4], blurred.pixel(0, 2));
+
+        // a Gaussian of zero width leaves the image as it is
+        let same = dot.convolve(&Gaussian { sigma: 0. });
+        assert_near([1.; 4], same.pixel(2, 2));
+        assert_near([0.; 4], same.pixel(1, 2));
+    }
+
+    #[test]
+    fn test_resample() {
+        // a 2x2 box average of a checkerboard is uniform gray
+        let checker = Image::from_fn(8, 6, |x, y| if (x + y) % 2 == 0 { [1.; 4] } else { [0., 0., 0., 1.] });
+        let half = checker.resample(4, 3, &BoxFilter { radius: 0.5 });
+        assert_eq!((4, 3), (half.width(), half.height()));
+        for y in 0..3 {
+            for x in 0..4 {
+                assert_near([0.5, 0.5, 0.5, 1.], half.pixel(x, y));
+            }
+        }
+
+        // magnifying a ramp with a tent interpolates it linearly
+        let ramp = Image::from_fn(4, 1, |x, _| [x as f32, 0., 0., 1.]);
+        let wide = ramp.resample(8, 1, &Tent { radius: 1. });
+        for x in 1..7 {
+            assert_near([(x as f32 + 0.5) / 2. - 0.5, 0., 0., 1.], wide.pixel(x, 0));
+        }
+        assert_near([0.5, 0., 0., 1.], ramp.sample(0.25, 0.5));
+    }
+
+    #[test]
+    fn test_premultiplied() {
+        // opaque red next to transparent green stays pure red when averaged
+        let bytes = [255, 0, 0, 255, 0, 255, 0, 0];
+        let image = Image::from_rgba8(2, 1, &bytes);
+        let half = image.resample(1, 1, &BoxFilter { radius: 0.5 });
+
+        assert_near([0.5, 0., 0., 0.5], half.pixel(0, 0));
+        assert_eq!(vec![255, 0, 0, 128], half.to_rgba8());
+
+        // 8-bit sRGB round trips
+        let bytes: Vec<u8> = (0..=255).flat_map(|c| [c, 255 - c, c / 2, 255]).collect();
+        assert_eq!(bytes, Image::from_rgba8(256, 1, &bytes).to_rgba8());
+    }
+
+    #[test]
+    fn test_mip_chain() {
+        let image = Image::from_fn(10, 3, |x, y| [x as f32, y as f32, 0., 1.]);
+        let chain = image.mip_chain(&MitchellNetravali::default());
+
+        let sizes: Vec<_> = chain.iter().map(|l| (l.width(), l.height())).collect();
+        assert_eq!(vec![(10, 3), (5, 1), (2, 1), (1, 1)], sizes);
+
+        // a linear ramp keeps its mean through every level
+        let mean = |l: &Image| (0..l.width()).map(|x| l.pixel(x, 0)[0]).sum::<f32>() / l.width() as f32;
+        for level in &chain[1..] {
+            assert!((mean(level) - 4.5).abs() < 0.2, "{}", mean(level));
+        }
+    }
+
+    #[test]
+    fn test_empty() {
+        let filter = MitchellNetravali::default();
+        for image in [Image::new(0, 0), Image::from_rgba8(0, 0, &[]), Image::new(0, 3), Image::new(2, 0)] {
+            assert_eq!([0.; 4], image.sample(0.5, 0.5));
+
+            let resized = image.resample(3, 2, &filter);
+            assert_eq!((3, 2), (resized.width(), resized.height()));
+            assert_eq!(vec![0; 3 * 2 * 4], resized.to_rgba8());
+
+            let same = image.convolve(&filter);
+            assert_eq!((image.width(), image.height()), (same.width(), same.height()));
+        }
+
+        assert_eq!(1, Image::new(0, 0).mip_chain(&filter).len());
+    }
+}
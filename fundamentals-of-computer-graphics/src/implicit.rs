@@ -0,0 +1,340 @@
+//! Implicit surfaces `f(p) = 0` (FCG 2.5) as signed distance functions,
+//! negative inside, and their polygonization with marching cubes.
+
+use crate::math::{BoundingBox, Vector3};
+use crate::mesh::Mesh;
+use std::collections::HashMap;
+
+pub trait Implicit {
+    fn value(&self, p: &Vector3) -> f32;
+
+    /// Points away from the inside, by central differences unless
+    /// overridden.
+    fn gradient(&self, p: &Vector3) -> Vector3 {
+        let h = 1e-3;
+        let d = |o: Vector3| (self.value(&(p + &o)) - self.value(&(p - &o))) / (2. * h);
+        Vector3::new(d(Vector3::new(h, 0., 0.)), d(Vector3::new(0., h, 0.)), d(Vector3::new(0., 0., h)))
+    }
+}
+
+impl<F: Fn(&Vector3) -> f32> Implicit for F {
+    fn value(&self, p: &Vector3) -> f32 {
+        self(p)
+    }
+}
+
+#[derive(PartialEq, Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Sphere {
+    pub center: Vector3,
+    pub radius: f32,
+}
+
+impl Implicit for Sphere {
+    fn value(&self, p: &Vector3) -> f32 {
+        (p - &self.center).norm() - self.radius
+    }
+}
+
+/// Axis-aligned box, `half_size` from its center along each axis.
+#[derive(PartialEq, Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Cuboid {
+    pub center: Vector3,
+    pub half_size: Vector3,
+}
+
+impl Implicit for Cuboid {
+    fn value(&self, p: &Vector3) -> f32 {
+        let d = p - &self.center;
+        let q = Vector3::new(d.x.abs(), d.y.abs(), d.z.abs());
+        let q = &q - &self.half_size;
+
+        let outside = q.max(&Vector3::new(0., 0., 0.)).norm();
+        let inside = q.x.max(q.y).max(q.z).min(0.);
+        outside + inside
+    }
+}
+
+/// Torus around the y axis through `center`, `major` from the axis to the
+/// middle of the tube of radius `minor`.
+#[derive(PartialEq, Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Torus {
+    pub center: Vector3,
+    pub major: f32,
+    pub minor: f32,
+}
+
+impl Implicit for Torus {
+    fn value(&self, p: &Vector3) -> f32 {
+        let d = p - &self.center;
+        let ring = (d.x * d.x + d.z * d.z).sqrt() - self.major;
+        (ring * ring + d.y * d.y).sqrt() - self.minor
+    }
+}
+
+/// Inside either shape.
+#[derive(PartialEq, Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Union<A, B>(pub A, pub B);
+
+/// Inside both shapes.
+#[derive(PartialEq, Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Intersection<A, B>(pub A, pub B);
+
+/// Inside the first shape but not the second.
+#[derive(PartialEq, Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Difference<A, B>(pub A, pub B);
+
+/// Union that rounds the seam over a distance of about `k`.
+#[derive(PartialEq, Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SmoothUnion<A, B> {
+    pub a: A,
+    pub b: B,
+    pub k: f32,
+}
+
+impl<A: Implicit, B: Implicit> Implicit for Union<A, B> {
+    fn value(&self, p: &Vector3) -> f32 {
+        self.0.value(p).min(self.1.value(p))
+    }
+}
+
+impl<A: Implicit, B: Implicit> Implicit for Intersection<A, B> {
+    fn value(&self, p: &Vector3) -> f32 {
+        self.0.value(p).max(self.1.value(p))
+    }
+}
+
+impl<A: Implicit, B: Implicit> Implicit for Difference<A, B> {
+    fn value(&self, p: &Vector3) -> f32 {
+        self.0.value(p).max(-self.1.value(p))
+    }
+}
+
+impl<A: Implicit, B: Implicit> Implicit for SmoothUnion<A, B> {
+    fn value(&self, p: &Vector3) -> f32 {
+        smooth_min(self.a.value(p), self.b.value(p), self.k)
+    }
+}
+
+/// Polynomial smooth minimum, equal to `a.min(b)` once they are more than
+/// `k` apart.
+pub fn smooth_min(a: f32, b: f32, k: f32) -> f32 {
+    if k <= 0. {
+        return a.min(b);
+    }
+    let h = (k - (a - b).abs()).max(0.) / k;
+    a.min(b) - h * h * k / 4.
+}
+
+/// The corners of each cube face, counter-clockwise when seen from outside
+/// the cube. Corner `c` is offset by `c & 1`, `c >> 1 & 1` and `c >> 2 & 1`
+/// cells along x, y and z.
+const FACES: [[usize; 4]; 6] = [[0, 4, 6, 2], [1, 3, 7, 5], [0, 1, 5, 4], [2, 6, 7, 3], [0, 2, 3, 1], [4, 5, 7, 6]];
+
+/// Polygonizes `f(p) = iso` inside `bounds`, sampled on a grid of
+/// `resolution` cells.
+///
+/// Instead of a 256-case table, each cube traces the contour across its six
+/// faces, keeping the inside on the left of each segment, and fans the
+/// resulting loops into triangles. Faces with an ambiguous sign pattern are
+/// resolved from the face center alone, so neighbouring cubes agree and the
+/// mesh has no cracks. Vertices on shared grid edges are shared between
+/// cubes and their normals are the normalized gradient of `f`.
+pub fn marching_cubes(f: &impl Implicit, bounds: &BoundingBox, resolution: [usize; 3], iso: f32) -> Mesh {
+    let [nx, ny, nz] = resolution;
+    let size = bounds.diagonal();
+    let step = Vector3::new(size.x / nx as f32, size.y / ny as f32, size.z / nz as f32);
+
+    let position = |[x, y, z]: [usize; 3]| {
+        Vector3::new(bounds.min.x + x as f32 * step.x, bounds.min.y + y as f32 * step.y, bounds.min.z + z as f32 * step.z)
+    };
+    let index = |[x, y, z]: [usize; 3]| x + (nx + 1) * (y + (ny + 1) * z);
+
+    let mut values = Vec::with_capacity((nx + 1) * (ny + 1) * (nz + 1));
+    for z in 0..=nz {
+        for y in 0..=ny {
+            for x in 0..=nx {
+                values.push(f.value(&position([x, y, z])));
+            }
+        }
+    }
+
+    let mut mesh = Mesh::new();
+    let mut shared = HashMap::new();
+    for z in 0..nz {
+        for y in 0..ny {
+            for x in 0..nx {
+                let corner = |c: usize| [x + (c & 1), y + (c >> 1 & 1), z + (c >> 2 & 1)];
+                let value = |c: usize| values[index(corner(c))];
+                let inside: [bool; 8] = std::array::from_fn(|c| value(c) < iso);
+                if inside.iter().all(|&i| i == inside[0]) {
+                    continue;
+                }
+
+                // Edges are named by their lower corner and axis.
+                let edge = |a: usize, b: usize| (a.min(b), (a ^ b).trailing_zeros() as usize);
+                let mut segments = Vec::new();
+                for face in FACES {
+                    let crossing: Vec<_> = (0..4)
+                        .filter(|&k| inside[face[k]] != inside[face[(k + 1) % 4]])
+                        .collect();
+
+                    // Each segment runs from where the face boundary leaves
+                    // the inside to where it enters it again.
+                    let face_edge = |k: usize| edge(face[k], face[(k + 1) % 4]);
+                    let directed = |a: usize, b: usize| {
+                        let (ka, kb) = (crossing[a], crossing[b]);
+                        if inside[face[ka]] { (face_edge(ka), face_edge(kb)) } else { (face_edge(kb), face_edge(ka)) }
+                    };
+                    match crossing.len() {
+                        2 => segments.push(directed(0, 1)),
+                        4 => {
+                            // Inside corners are connected through the face
+                            // when its center is inside, otherwise cut off.
+                            // Summing diagonals first gives neighbouring
+                            // cubes the same rounding.
+                            let center = ((value(face[0]) + value(face[2])) + (value(face[1]) + value(face[3]))) / 4.;
+                            if inside[face[1]] != (center < iso) {
+                                segments.extend([directed(0, 1), directed(2, 3)]);
+                            } else {
+                                segments.extend([directed(1, 2), directed(3, 0)]);
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+
+                let mut vertex = |mesh: &mut Mesh, (a, axis): (usize, usize)| {
+                    *shared.entry((corner(a), axis)).or_insert_with(|| {
+                        let b = a | 1 << axis;
+                        let (pa, pb) = (position(corner(a)), position(corner(b)));
+                        let t = (iso - value(a)) / (value(b) - value(a));
+                        let p = &pa + &(t * &(&pb - &pa));
+                        mesh.push_vertex(p, f.gradient(&p).normalize())
+                    })
+                };
+
+                while let Some((start, mut next)) = segments.pop() {
+                    let mut polygon = vec![vertex(&mut mesh, start)];
+                    while next != start {
+                        polygon.push(vertex(&mut mesh, next));
+                        let k = segments.iter().position(|&(a, _)| a == next).unwrap();
+                        next = segments.swap_remove(k).1;
+                    }
+
+                    for k in 1..polygon.len() - 1 {
+                        mesh.triangles.push([polygon[0], polygon[k + 1], polygon[k]]);
+                    }
+                }
+            }
+        }
+    }
+    mesh
+}
+
+#[cfg(test)]
+mod test_implicit {
+    use super::*;
+    use crate::math::vec3;
+    use std::collections::HashSet;
+
+    /// Every edge is shared by exactly two triangles with opposite
+    /// directions, i.e. the mesh is closed and consistently wound. Returns
+    /// the Euler characteristic.
+    fn assert_closed(mesh: &Mesh) -> i64 {
+        let mut directed = HashSet::new();
+        for t in &mesh.triangles {
+            for k in 0..3 {
+                assert!(directed.insert((t[k], t[(k + 1) % 3])), "edge used twice in the same direction");
+            }
+        }
+        for &(a, b) in &directed {
+            assert!(directed.contains(&(b, a)), "open edge {a}-{b}");
+        }
+
+        let (v, e, f) = (mesh.positions.len(), directed.len() / 2, mesh.triangles.len());
+        v as i64 - e as i64 + f as i64
+    }
+
+    #[test]
+    fn test_primitives() {
+        let sphere = Sphere { center: vec3(1., 0., 0.), radius: 2. };
+        assert_eq!(-2., sphere.value(&vec3(1., 0., 0.)));
+        assert_eq!(1., sphere.value(&vec3(1., 3., 0.)));
+
+        let cuboid = Cuboid { center: vec3(0., 0., 0.), half_size: vec3(1., 2., 3.) };
+        assert_eq!(-1., cuboid.value(&vec3(0., 0., 0.)));
+        assert_eq!(1., cuboid.value(&vec3(0., 3., 0.)));
+        assert_eq!(2f32.sqrt(), cuboid.value(&vec3(2., 3., 0.)));
+
+        let torus = Torus { center: vec3(0., 0., 0.), major: 2., minor: 0.5 };
+        assert_eq!(-0.5, torus.value(&vec3(0., 0., 2.)));
+        assert_eq!(1.5, torus.value(&vec3(0., 0., 0.)));
+        assert_eq!(0., torus.value(&vec3(-2., 0.5, 0.)));
+
+        let g = sphere.gradient(&vec3(1., 3., 0.));
+        assert!((&g - &vec3(0., 1., 0.)).norm() < 1e-2);
+    }
+
+    #[test]
+    fn test_csg() {
+        let a = Sphere { center: vec3(-1., 0., 0.), radius: 1.5 };
+        let b = Sphere { center: vec3(1., 0., 0.), radius: 1.5 };
+        let p = vec3(1., 0., 0.);
+
+        assert_eq!(-1.5, Union(a, b).value(&p));
+        assert_eq!(0.5, Intersection(a, b).value(&p));
+        assert_eq!(1.5, Difference(a, b).value(&p));
+        assert!(SmoothUnion { a, b, k: 1. }.value(&vec3(0., 1., 0.)) < Union(a, b).value(&vec3(0., 1., 0.)));
+
+        assert_eq!(1., smooth_min(1., 3., 1.));
+        assert_eq!(0.75, smooth_min(1., 1., 1.));
+        assert_eq!(1., smooth_min(1., 1., 0.));
+    }
+
+    #[test]
+    fn test_marching_cubes_sphere() {
+        let sphere = Sphere { center: vec3(0.1, 0., -0.05), radius: 1. };
+        let bounds = BoundingBox::new(&vec3(-1.5, -1.5, -1.5), &vec3(1.5, 1.5, 1.5));
+        let mesh = marching_cubes(&sphere, &bounds, [20, 20, 20], 0.);
+
+        assert!(!mesh.triangles.is_empty());
+        assert_eq!(2, assert_closed(&mesh));
+
+        for (p, n) in mesh.positions.iter().zip(&mesh.normals) {
+            let radial = p - &sphere.center;
+            assert!((radial.norm() - 1.).abs() < 0.02, "{:?}", p);
+            assert!(n.dot(&radial.normalize()) > 0.99);
+        }
+
+        for i in 0..mesh.triangles.len() {
+            let [a, b, c] = mesh.triangle(i);
+            let facing = (&b - &a).cross(&(&c - &a));
+            assert!(facing.dot(&(&a - &sphere.center)) >= 0., "triangle {i} faces inwards");
+        }
+    }
+
+    #[test]
+    fn test_marching_cubes_shapes() {
+        let bounds = BoundingBox::new(&vec3(-3., -3., -3.), &vec3(3., 3., 3.));
+
+        let torus = Torus { center: vec3(0., 0., 0.), major: 1.6, minor: 0.6 };
+        assert_eq!(0, assert_closed(&marching_cubes(&torus, &bounds, [24, 24, 24], 0.)));
+
+        // Two blobs merge into one shape, or stay apart without blending.
+        let a = Sphere { center: vec3(-1.1, 0., 0.), radius: 1. };
+        let b = Sphere { center: vec3(1.1, 0.05, 0.), radius: 1. };
+        assert_eq!(2, assert_closed(&marching_cubes(&SmoothUnion { a, b, k: 0.8 }, &bounds, [24, 24, 24], 0.)));
+        assert_eq!(4, assert_closed(&marching_cubes(&Union(a, b), &bounds, [24, 24, 24], 0.)));
+
+        // Cutting a ball out of a box leaves its eight corners.
+        let cut = Difference(Cuboid { center: vec3(0., 0., 0.), half_size: vec3(2., 2., 2.) }, |p: &Vector3| p.norm() - 2.9);
+        assert_eq!(16, assert_closed(&marching_cubes(&cut, &bounds, [40, 40, 40], 0.)));
+    }
+}
@@ -1,2 +1,12 @@
+pub mod bvh;
+pub mod camera;
+pub mod color;
+pub mod curves;
+pub mod image;
+pub mod implicit;
 pub mod math;
-pub mod raytracing;
\ No newline at end of file
+pub mod mesh;
+pub mod rasterization;
+pub mod raytracing;
+pub mod sampling;
+pub mod triangulation;
@@ -0,0 +1,288 @@
+use crate::math::{Mat4, Scalar, Vec3};
+
+/// Axis-aligned bounding box. An empty box has `min > max` so that it is the
+/// identity for `union`.
+#[derive(PartialEq, Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Aabb<T> {
+    pub min: Vec3<T>,
+    pub max: Vec3<T>,
+}
+
+pub type BoundingBox = Aabb<f32>;
+pub type BoundingBoxd = Aabb<f64>;
+
+impl<T: Scalar> Aabb<T> {
+    pub const EMPTY: Self = Self {
+        min: Vec3 {
+            x: T::INFINITY,
+            y: T::INFINITY,
+            z: T::INFINITY,
+        },
+        max: Vec3 {
+            x: T::NEG_INFINITY,
+            y: T::NEG_INFINITY,
+            z: T::NEG_INFINITY,
+        },
+    };
+
+    /// Box spanned by two opposite corners, in any order.
+    pub fn new(a: &Vec3<T>, b: &Vec3<T>) -> Self {
+        Self {
+            min: a.min(b),
+            max: a.max(b),
+        }
+    }
+
+    pub fn from_points<'a>(points: impl IntoIterator<Item = &'a Vec3<T>>) -> Self {
+        points.into_iter().fold(Self::EMPTY, |b, p| b.grow(p))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.min.x > self.max.x || self.min.y > self.max.y || self.min.z > self.max.z
+    }
+
+    pub fn union(&self, rhs: &Self) -> Self {
+        Self {
+            min: self.min.min(&rhs.min),
+            max: self.max.max(&rhs.max),
+        }
+    }
+
+    /// Smallest box containing both `self` and `p`.
+    pub fn grow(&self, p: &Vec3<T>) -> Self {
+        Self {
+            min: self.min.min(p),
+            max: self.max.max(p),
+        }
+    }
+
+    /// Whether `p` is inside or on the boundary.
+    pub fn contains(&self, p: &Vec3<T>) -> bool {
+        (0..3).all(|i| self.min[i] <= p[i] && p[i] <= self.max[i])
+    }
+
+    pub fn diagonal(&self) -> Vec3<T> {
+        &self.max - &self.min
+    }
+
+    pub fn centroid(&self) -> Vec3<T> {
+        &(&self.min + &self.max) * T::HALF
+    }
+
+    pub fn surface_area(&self) -> T {
+        if self.is_empty() {
+            return T::ZERO;
+        }
+        let d = self.diagonal();
+        T::TWO * (d.x * d.y + d.y * d.z + d.z * d.x)
+    }
+
+    /// Index of the longest side, 0 for x, 1 for y and 2 for z.
+    pub fn longest_axis(&self) -> usize {
+        let d = self.diagonal();
+        if d.x > d.y && d.x > d.z {
+            0
+        } else if d.y > d.z {
+            1
+        } else {
+            2
+        }
+    }
+
+    pub fn corners(&self) -> [Vec3<T>; 8] {
+        let (a, b) = (&self.min, &self.max);
+        [
+            Vec3::new(a.x, a.y, a.z),
+            Vec3::new(b.x, a.y, a.z),
+            Vec3::new(a.x, b.y, a.z),
+            Vec3::new(b.x, b.y, a.z),
+            Vec3::new(a.x, a.y, b.z),
+            Vec3::new(b.x, a.y, b.z),
+            Vec3::new(a.x, b.y, b.z),
+            Vec3::new(b.x, b.y, b.z),
+        ]
+    }
+
+    /// Bounds of the box after transformation by `m`.
+    pub fn transform(&self, m: &Mat4<T>) -> Self {
+        if self.is_empty() {
+            return *self;
+        }
+        self.corners()
+            .iter()
+            .fold(Self::EMPTY, |b, p| b.grow(&m.transform_point(p)))
+    }
+
+    /// Slab test for the ray `e + td`, returning the parameter interval
+    /// inside the box clipped to `[t0, t1]`.
+    ///
+    /// Zero direction components and rays starting on a slab boundary are
+    /// handled through IEEE infinities, and the far distance is rounded up so
+    /// rays grazing an edge are not lost to floating-point error (PBRT 3.9.2).
+    /// An empty box is never hit.
+    pub fn intersect_ray(&self, e: &Vec3<T>, d: &Vec3<T>, t0: T, t1: T) -> Option<(T, T)> {
+        if self.is_empty() {
+            return None;
+        }
+
+        // 1 + 2γ₃, bounds the rounding error of the three operations below
+        let gamma = T::from_f64(3.) * T::EPSILON * T::HALF;
+        let round_up = T::ONE + T::TWO * gamma / (T::ONE - gamma);
+
+        let (mut t0, mut t1) = (t0, t1);
+        for i in 0..3 {
+            let inv = T::ONE / d[i];
+            let mut near = (self.min[i] - e[i]) * inv;
+            let mut far = (self.max[i] - e[i]) * inv;
+            if near > far {
+                std::mem::swap(&mut near, &mut far);
+            }
+            far *= round_up;
+
+            // Scalar::max/min ignore the NaN from 0 * inf
+            t0 = t0.max(near);
+            t1 = t1.min(far);
+            if t0 > t1 {
+                return None;
+            }
+        }
+        Some((t0, t1))
+    }
+
+    /// Converts the corners to another precision. Narrowing rounds to the
+    /// nearest value, so the result need not contain the original box.
+    pub fn cast<U: Scalar>(&self) -> Aabb<U> {
+        Aabb {
+            min: self.min.cast(),
+            max: self.max.cast(),
+        }
+    }
+}
+
+impl From<BoundingBox> for BoundingBoxd {
+    fn from(b: BoundingBox) -> Self {
+        b.cast()
+    }
+}
+
+#[cfg(test)]
+mod test_aabb {
+    use super::*;
+    use crate::math::{Transform, Vector3, Vector3d};
+
+    fn unit() -> BoundingBox {
+        BoundingBox::new(&Vector3::new(-1., -1., -1.), &Vector3::new(1., 1., 1.))
+    }
+
+    #[test]
+    fn test_union() {
+        let a = BoundingBox::new(&Vector3::new(1., 1., 1.), &Vector3::new(0., 0., 0.));
+        let b = BoundingBox::new(&Vector3::new(2., -1., 0.), &Vector3::new(3., 0., 0.5));
+
+        assert_eq!(Vector3::new(0., 0., 0.), a.min);
+        assert_eq!(BoundingBox::new(&Vector3::new(0., -1., 0.), &Vector3::new(3., 1., 1.)), a.union(&b));
+        assert_eq!(a, a.union(&BoundingBox::EMPTY));
+        assert_eq!(a, BoundingBox::EMPTY.union(&a));
+        assert!(BoundingBox::EMPTY.is_empty());
+        assert!(!a.is_empty());
+    }
+
+    #[test]
+    fn test_points() {
+        let points = [Vector3::new(1., 0., 0.), Vector3::new(0., 2., 0.), Vector3::new(0., 0., -3.)];
+        let b = BoundingBox::from_points(points.iter());
+
+        assert_eq!(BoundingBox::new(&Vector3::new(0., 0., -3.), &Vector3::new(1., 2., 0.)), b);
+        assert!(points.iter().all(|p| b.contains(p)));
+        assert!(b.contains(&Vector3::new(0.5, 0.5, -1.)));
+        assert!(!b.contains(&Vector3::new(0.5, 0.5, 1.)));
+        assert!(!BoundingBox::EMPTY.contains(&Vector3::new(0., 0., 0.)));
+    }
+
+    #[test]
+    fn test_measures() {
+        let b = BoundingBox::new(&Vector3::new(0., 0., 0.), &Vector3::new(1., 2., 3.));
+
+        assert_eq!(22., b.surface_area());
+        assert_eq!(Vector3::new(0.5, 1., 1.5), b.centroid());
+        assert_eq!(2, b.longest_axis());
+        assert_eq!(0., BoundingBox::EMPTY.surface_area());
+        assert_eq!(24., unit().surface_area());
+    }
+
+    #[test]
+    fn test_intersect_ray() {
+        let b = unit();
+
+        let hit = b.intersect_ray(&Vector3::new(-5., 0., 0.), &Vector3::new(1., 0., 0.), 0., f32::INFINITY);
+        let (t0, t1) = hit.unwrap();
+        assert!((t0 - 4.).abs() < 1e-5 && (t1 - 6.).abs() < 1e-5);
+
+        // behind the origin, or outside [t0, t1]
+        assert_eq!(None, b.intersect_ray(&Vector3::new(5., 0., 0.), &Vector3::new(1., 0., 0.), 0., f32::INFINITY));
+        assert_eq!(None, b.intersect_ray(&Vector3::new(-5., 0., 0.), &Vector3::new(1., 0., 0.), 0., 3.));
+
+        // misses
+        assert_eq!(None, b.intersect_ray(&Vector3::new(-5., 2., 0.), &Vector3::new(1., 0., 0.), 0., f32::INFINITY));
+        assert_eq!(None, b.intersect_ray(&Vector3::new(-5., 0., 0.), &Vector3::new(1., 1., 0.), 0., f32::INFINITY));
+
+        // starting inside
+        let (t0, t1) = b.intersect_ray(&Vector3::new(0., 0., 0.), &Vector3::new(0., 0., -2.), 0., f32::INFINITY).unwrap();
+        assert_eq!(0., t0);
+        assert!((t1 - 0.5).abs() < 1e-5);
+
+        // diagonal
+        assert!(b.intersect_ray(&Vector3::new(-2., -2., -2.), &Vector3::new(1., 1., 1.), 0., f32::INFINITY).is_some());
+    }
+
+    #[test]
+    fn test_intersect_ray_degenerate() {
+        let b = unit();
+
+        // parallel to a slab, lying exactly on its boundary: 0 * inf = NaN
+        let hit = b.intersect_ray(&Vector3::new(-5., 1., 0.), &Vector3::new(1., 0., 0.), 0., f32::INFINITY);
+        assert!(hit.is_some());
+
+        // grazing an edge
+        let hit = b.intersect_ray(&Vector3::new(-5., 1., 1.), &Vector3::new(1., 0., 0.), 0., f32::INFINITY);
+        assert!(hit.is_some());
+
+        // flat box, e.g. around an axis-aligned triangle
+        let flat = BoundingBox::new(&Vector3::new(-1., -1., 0.), &Vector3::new(1., 1., 0.));
+        assert!(flat.intersect_ray(&Vector3::new(0.2, 0.3, 5.), &Vector3::new(0., 0., -1.), 0., f32::INFINITY).is_some());
+
+        // the infinite slabs of the empty box must not add up to a hit
+        assert_eq!(None, BoundingBox::EMPTY.intersect_ray(&Vector3::new(0., 0., 0.), &Vector3::new(1., 0., 0.), 0., 10.));
+    }
+
+    #[test]
+    fn test_transform() {
+        let b = BoundingBox::new(&Vector3::new(0., 0., 0.), &Vector3::new(2., 1., 1.));
+
+        let t = Transform::translate(&Vector3::new(1., 2., 3.));
+        assert_eq!(BoundingBox::new(&Vector3::new(1., 2., 3.), &Vector3::new(3., 3., 4.)), b.transform(t.matrix()));
+
+        // rotating 90° about z swaps the x and y extents
+        let r = b.transform(Transform::rotate_z(std::f32::consts::FRAC_PI_2).matrix());
+        let d = r.diagonal();
+        assert!((d.x - 1.).abs() < 1e-5 && (d.y - 2.).abs() < 1e-5 && (d.z - 1.).abs() < 1e-5);
+
+        assert!(BoundingBox::EMPTY.transform(t.matrix()).is_empty());
+    }
+
+    #[test]
+    fn test_precision() {
+        let b = BoundingBox::new(&Vector3::new(0.1, 0.2, 0.3), &Vector3::new(1., 2., 3.));
+        assert_eq!(b, BoundingBoxd::from(b).cast());
+        assert_eq!(BoundingBoxd::EMPTY, BoundingBox::EMPTY.into());
+
+        // a box far from the origin, too thin for f32 to resolve
+        let far = BoundingBoxd::new(&Vector3d::new(1e8, 0., 0.), &Vector3d::new(1e8 + 0.5, 1., 1.));
+        assert_eq!(0.5, far.diagonal().x);
+        assert_eq!(0., far.cast::<f32>().diagonal().x);
+
+        let (t0, t1) = far.intersect_ray(&Vector3d::new(0., 0.5, 0.5), &Vector3d::new(1., 0., 0.), 0., f64::INFINITY).unwrap();
+        assert!((t1 - t0 - 0.5).abs() < 1e-6);
+    }
+}
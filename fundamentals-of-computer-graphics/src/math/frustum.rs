@@ -0,0 +1,190 @@
+use crate::math::{BoundingBox, Matrix4, Vector3, Vector4};
+
+/// Where a point or bounding volume lies relative to a `Frustum`.
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+pub enum Containment {
+    Inside,
+    Outside,
+    Intersecting,
+}
+
+/// Six planes bounding a view volume, each `(n, d)` with a unit normal `n`
+/// pointing inwards, so `n · p + d` is the signed distance of `p` from it.
+///
+/// The planes are in the space the matrix maps from: camera space for a
+/// projection, world space for a projection-view matrix.
+///
+/// Only f32: it culls against the same matrices handed to the GPU, and an
+/// answer off by a rounding error at a plane only makes culling looser.
+#[derive(PartialEq, Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Frustum {
+    pub planes: [Vector4; 6],
+}
+
+impl Frustum {
+    /// Extracts the planes bounding `-w <= x, y, z <= w` in clip space
+    /// (Gribb and Hartmann). Works for any convention, FCG's with w < 0 in
+    /// front of the camera as well as OpenGL's or Direct3D's; for the latter
+    /// the z range is wider than needed, which only makes culling looser.
+    pub fn from_matrix(m: &Matrix4) -> Self {
+        let [r0, r1, r2, r3] = [0, 1, 2, 3].map(|r| m.row(r));
+        let plane = |s: f32, r: [f32; 4]| Vector4::new(r3[0] + s * r[0], r3[1] + s * r[1], r3[2] + s * r[2], r3[3] + s * r[3]);
+        let planes = [plane(1., r0), plane(-1., r0), plane(1., r1), plane(-1., r1), plane(1., r2), plane(-1., r2)];
+
+        // The clip-space origin maps back to an interior point h with every
+        // plane at h equal to 1, so the planes face inwards exactly when h
+        // has a positive w.
+        let flip = m.inverse().is_some_and(|inv| inv[(3, 3)] < 0.);
+
+        Self {
+            planes: planes.map(|p| {
+                let s = (if flip { -1. } else { 1. }) / p.truncate().norm();
+                Vector4::new(s * p.x, s * p.y, s * p.z, s * p.w)
+            }),
+        }
+    }
+
+    /// Camera-space frustum of `Matrix4::perspective_fov`: vertical field of
+    /// view `fovy`, `aspect` = width / height, and `0 > n > f`.
+    pub fn perspective(fovy: f32, aspect: f32, n: f32, f: f32) -> Self {
+        Self::from_matrix(&Matrix4::perspective_fov(fovy, aspect, n, f))
+    }
+
+    /// Signed distances of `p` from each plane, positive inside.
+    fn distances(&self, p: &Vector3) -> [f32; 6] {
+        self.planes.map(|plane| plane.truncate().dot(p) + plane.w)
+    }
+
+    /// `Intersecting` when `p` lies exactly on a boundary plane.
+    pub fn classify_point(&self, p: &Vector3) -> Containment {
+        let distances = self.distances(p);
+        if distances.iter().any(|&d| d < 0.) {
+            Containment::Outside
+        } else if distances.contains(&0.) {
+            Containment::Intersecting
+        } else {
+            Containment::Inside
+        }
+    }
+
+    pub fn classify_sphere(&self, center: &Vector3, radius: f32) -> Containment {
+        let distances = self.distances(center);
+        if distances.iter().any(|&d| d < -radius) {
+            Containment::Outside
+        } else if distances.iter().any(|&d| d < radius) {
+            Containment::Intersecting
+        } else {
+            Containment::Inside
+        }
+    }
+
+    /// Tests the corners furthest along and against each plane normal. Boxes
+    /// outside the frustum near its edges may be reported as `Intersecting`,
+    /// which is conservative for culling.
+    pub fn classify_aabb(&self, aabb: &BoundingBox) -> Containment {
+        let mut containment = Containment::Inside;
+        for plane in &self.planes {
+            let n = plane.truncate();
+            let pick = |a: f32, b: f32, toward: bool| if toward { a } else { b };
+
+            let farthest = Vector3::new(
+                pick(aabb.max.x, aabb.min.x, n.x >= 0.),
+                pick(aabb.max.y, aabb.min.y, n.y >= 0.),
+                pick(aabb.max.z, aabb.min.z, n.z >= 0.),
+            );
+            if n.dot(&farthest) + plane.w < 0. {
+                return Containment::Outside;
+            }
+
+            let nearest = Vector3::new(
+                pick(aabb.min.x, aabb.max.x, n.x >= 0.),
+                pick(aabb.min.y, aabb.max.y, n.y >= 0.),
+                pick(aabb.min.z, aabb.max.z, n.z >= 0.),
+            );
+            if n.dot(&nearest) + plane.w < 0. {
+                containment = Containment::Intersecting;
+            }
+        }
+        containment
+    }
+}
+
+#[cfg(test)]
+mod test_frustum {
+    use super::*;
+    use std::f32::consts::FRAC_PI_2;
+
+    #[test]
+    fn test_perspective() {
+        // 90° both ways, so the sides are the planes |x| = -z and |y| = -z
+        let frustum = Frustum::perspective(FRAC_PI_2, 1., -1., -10.);
+
+        assert_eq!(Containment::Inside, frustum.classify_point(&Vector3::new(0., 0., -5.)));
+        assert_eq!(Containment::Inside, frustum.classify_point(&Vector3::new(4.9, -4.9, -5.)));
+        assert_eq!(Containment::Outside, frustum.classify_point(&Vector3::new(5.1, 0., -5.)));
+        assert_eq!(Containment::Outside, frustum.classify_point(&Vector3::new(0., 0., 5.)));
+        assert_eq!(Containment::Outside, frustum.classify_point(&Vector3::new(0., 0., -0.5)));
+        assert_eq!(Containment::Outside, frustum.classify_point(&Vector3::new(0., 0., -11.)));
+
+        for plane in &frustum.planes {
+            assert!((plane.truncate().norm() - 1.).abs() < 1e-6);
+        }
+
+        assert_eq!(Containment::Inside, frustum.classify_sphere(&Vector3::new(0., 0., -5.), 1.));
+        assert_eq!(Containment::Intersecting, frustum.classify_sphere(&Vector3::new(5., 0., -5.), 1.));
+        assert_eq!(Containment::Intersecting, frustum.classify_sphere(&Vector3::new(0., 0., -10.), 0.5));
+        assert_eq!(Containment::Outside, frustum.classify_sphere(&Vector3::new(7., 0., -5.), 1.));
+        assert_eq!(Containment::Outside, frustum.classify_sphere(&Vector3::new(0., 0., 2.), 1.));
+    }
+
+    #[test]
+    fn test_aabb() {
+        let frustum = Frustum::perspective(FRAC_PI_2, 1., -1., -10.);
+        let aabb = |min: [f32; 3], max: [f32; 3]| BoundingBox::new(&Vector3::new(min[0], min[1], min[2]), &Vector3::new(max[0], max[1], max[2]));
+
+        assert_eq!(Containment::Inside, frustum.classify_aabb(&aabb([-1., -1., -6.], [1., 1., -4.])));
+        assert_eq!(Containment::Intersecting, frustum.classify_aabb(&aabb([4., -1., -6.], [6., 1., -4.])));
+        assert_eq!(Containment::Intersecting, frustum.classify_aabb(&aabb([-20., -20., -20.], [20., 20., 20.])));
+        assert_eq!(Containment::Outside, frustum.classify_aabb(&aabb([7., -1., -6.], [9., 1., -4.])));
+        assert_eq!(Containment::Outside, frustum.classify_aabb(&aabb([-1., -1., 1.], [1., 1., 3.])));
+        assert_eq!(Containment::Outside, frustum.classify_aabb(&aabb([-1., -1., -13.], [1., 1., -11.])));
+    }
+
+    #[test]
+    fn test_projection_view() {
+        // a camera at (10, 0, 0) looking down -x sees the origin
+        let view = Matrix4::camera(&Vector3::new(10., 0., 0.), &Vector3::new(-1., 0., 0.), &Vector3::new(0., 1., 0.));
+        let frustum = Frustum::from_matrix(&(&Matrix4::perspective_fov(FRAC_PI_2, 2., -1., -100.) * &view));
+
+        assert_eq!(Containment::Inside, frustum.classify_point(&Vector3::new(0., 0., 0.)));
+        assert_eq!(Containment::Inside, frustum.classify_point(&Vector3::new(0., 0., 15.)));
+        assert_eq!(Containment::Outside, frustum.classify_point(&Vector3::new(0., 15., 0.)));
+        assert_eq!(Containment::Outside, frustum.classify_point(&Vector3::new(20., 0., 0.)));
+        assert_eq!(Containment::Intersecting, frustum.classify_sphere(&Vector3::new(0., 10., 0.), 1.));
+
+        // OpenGL's convention, w = -z > 0 in front of the camera
+        let (n, f) = (1., 100.);
+        let gl = Matrix4::from_rows([
+            [1., 0., 0., 0.],
+            [0., 1., 0., 0.],
+            [0., 0., -(f + n) / (f - n), -2. * f * n / (f - n)],
+            [0., 0., -1., 0.],
+        ]);
+        let frustum = Frustum::from_matrix(&(&gl * &view));
+
+        assert_eq!(Containment::Inside, frustum.classify_point(&Vector3::new(0., 0., 0.)));
+        assert_eq!(Containment::Outside, frustum.classify_point(&Vector3::new(0., 15., 0.)));
+        assert_eq!(Containment::Outside, frustum.classify_point(&Vector3::new(20., 0., 0.)));
+    }
+
+    #[test]
+    fn test_orthographic() {
+        let frustum = Frustum::from_matrix(&Matrix4::orthographic(-2., 2., -1., 1., -1., -5.));
+
+        assert_eq!(Containment::Inside, frustum.classify_point(&Vector3::new(1.5, 0.5, -3.)));
+        assert_eq!(Containment::Intersecting, frustum.classify_point(&Vector3::new(2., 0.5, -3.)));
+        assert_eq!(Containment::Outside, frustum.classify_point(&Vector3::new(0., 1.5, -3.)));
+        assert_eq!(Containment::Outside, frustum.classify_point(&Vector3::new(0., 0., -6.)));
+    }
+}
@@ -0,0 +1,228 @@
+//! Conversions to and from other math crates, each behind a feature of the
+//! same name. Matrices are column-major everywhere, so they convert without
+//! transposing.
+
+#[cfg(feature = "mint")]
+mod mint_conversions {
+    use crate::math::{Mat3, Mat4, Quat, Scalar, Vec2, Vec3, Vec4};
+
+    macro_rules! impl_mint_vector {
+        ($v:ident, $m:ident { $($c:ident),+ }) => {
+            impl<T: Scalar> From<mint::$m<T>> for $v<T> {
+                fn from(v: mint::$m<T>) -> Self {
+                    Self { $($c: v.$c),+ }
+                }
+            }
+
+            impl<T: Scalar> From<$v<T>> for mint::$m<T> {
+                fn from(v: $v<T>) -> Self {
+                    Self { $($c: v.$c),+ }
+                }
+            }
+        };
+    }
+
+    impl_mint_vector!(Vec2, Vector2 { x, y });
+    impl_mint_vector!(Vec3, Vector3 { x, y, z });
+    impl_mint_vector!(Vec4, Vector4 { x, y, z, w });
+    impl_mint_vector!(Vec3, Point3 { x, y, z });
+
+    impl<T: Scalar> From<mint::Quaternion<T>> for Quat<T> {
+        fn from(q: mint::Quaternion<T>) -> Self {
+            Self::new(q.v.x, q.v.y, q.v.z, q.s)
+        }
+    }
+
+    impl<T: Scalar> From<Quat<T>> for mint::Quaternion<T> {
+        fn from(q: Quat<T>) -> Self {
+            Self { v: mint::Vector3 { x: q.x, y: q.y, z: q.z }, s: q.w }
+        }
+    }
+
+    impl<T: Scalar> From<mint::ColumnMatrix3<T>> for Mat3<T> {
+        fn from(m: mint::ColumnMatrix3<T>) -> Self {
+            Self::from_cols([m.x, m.y, m.z].map(|c| [c.x, c.y, c.z]))
+        }
+    }
+
+    impl<T: Scalar> From<Mat3<T>> for mint::ColumnMatrix3<T> {
+        fn from(m: Mat3<T>) -> Self {
+            let [x, y, z] = m.cols.map(mint::Vector3::from);
+            Self { x, y, z }
+        }
+    }
+
+    impl<T: Scalar> From<mint::ColumnMatrix4<T>> for Mat4<T> {
+        fn from(m: mint::ColumnMatrix4<T>) -> Self {
+            Self::from_cols([m.x, m.y, m.z, m.w].map(|c| [c.x, c.y, c.z, c.w]))
+        }
+    }
+
+    impl<T: Scalar> From<Mat4<T>> for mint::ColumnMatrix4<T> {
+        fn from(m: Mat4<T>) -> Self {
+            let [x, y, z, w] = m.cols.map(mint::Vector4::from);
+            Self { x, y, z, w }
+        }
+    }
+}
+
+#[cfg(feature = "nalgebra-glm")]
+mod glm_conversions {
+    use crate::math::{Mat3, Mat4, Quat, Vec2, Vec3, Vec4};
+    use nalgebra_glm as glm;
+
+    // nalgebra has its own scalar trait, so these are spelled out per type.
+    macro_rules! impl_glm {
+        ($t:ident) => {
+            impl From<glm::TVec2<$t>> for Vec2<$t> {
+                fn from(v: glm::TVec2<$t>) -> Self {
+                    Self::new(v.x, v.y)
+                }
+            }
+
+            impl From<Vec2<$t>> for glm::TVec2<$t> {
+                fn from(v: Vec2<$t>) -> Self {
+                    Self::new(v.x, v.y)
+                }
+            }
+
+            impl From<glm::TVec3<$t>> for Vec3<$t> {
+                fn from(v: glm::TVec3<$t>) -> Self {
+                    Self::new(v.x, v.y, v.z)
+                }
+            }
+
+            impl From<Vec3<$t>> for glm::TVec3<$t> {
+                fn from(v: Vec3<$t>) -> Self {
+                    Self::new(v.x, v.y, v.z)
+                }
+            }
+
+            impl From<glm::TVec4<$t>> for Vec4<$t> {
+                fn from(v: glm::TVec4<$t>) -> Self {
+                    Self::new(v.x, v.y, v.z, v.w)
+                }
+            }
+
+            impl From<Vec4<$t>> for glm::TVec4<$t> {
+                fn from(v: Vec4<$t>) -> Self {
+                    Self::new(v.x, v.y, v.z, v.w)
+                }
+            }
+
+            impl From<glm::Qua<$t>> for Quat<$t> {
+                fn from(q: glm::Qua<$t>) -> Self {
+                    Self::new(q.coords.x, q.coords.y, q.coords.z, q.coords.w)
+                }
+            }
+
+            impl From<Quat<$t>> for glm::Qua<$t> {
+                fn from(q: Quat<$t>) -> Self {
+                    glm::quat(q.x, q.y, q.z, q.w)
+                }
+            }
+
+            impl From<glm::TMat3<$t>> for Mat3<$t> {
+                fn from(m: glm::TMat3<$t>) -> Self {
+                    Self::from_cols(std::array::from_fn(|c| std::array::from_fn(|r| m[(r, c)])))
+                }
+            }
+
+            impl From<Mat3<$t>> for glm::TMat3<$t> {
+                fn from(m: Mat3<$t>) -> Self {
+                    Self::from_fn(|r, c| m.cols[c][r])
+                }
+            }
+
+            impl From<glm::TMat4<$t>> for Mat4<$t> {
+                fn from(m: glm::TMat4<$t>) -> Self {
+                    Self::from_cols(std::array::from_fn(|c| std::array::from_fn(|r| m[(r, c)])))
+                }
+            }
+
+            impl From<Mat4<$t>> for glm::TMat4<$t> {
+                fn from(m: Mat4<$t>) -> Self {
+                    Self::from_fn(|r, c| m.cols[c][r])
+                }
+            }
+        };
+    }
+
+    impl_glm!(f32);
+    impl_glm!(f64);
+}
+
+#[cfg(all(test, feature = "mint"))]
+mod test_mint {
+    use crate::math::{Matrix3, Matrix4, Quaternion, Vector2, Vector3, Vector4d};
+
+    #[test]
+    fn test_vectors() {
+        let v = Vector3::new(1., 2., 3.);
+        let m: mint::Vector3<f32> = v.into();
+
+        assert_eq!((1., 2., 3.), (m.x, m.y, m.z));
+        assert_eq!(v, Vector3::from(m));
+        assert_eq!(v, Vector3::from(mint::Point3 { x: 1., y: 2., z: 3. }));
+        assert_eq!(Vector2::new(1., 2.), mint::Vector2::from(Vector2::new(1., 2.)).into());
+        assert_eq!(Vector4d::new(1., 2., 3., 4.), mint::Vector4::from(Vector4d::new(1., 2., 3., 4.)).into());
+    }
+
+    #[test]
+    fn test_matrices() {
+        let m = Matrix4::from_rows([[1., 2., 3., 4.], [5., 6., 7., 8.], [9., 10., 11., 12.], [13., 14., 15., 16.]]);
+        let c: mint::ColumnMatrix4<f32> = m.into();
+
+        // The first column, not row.
+        assert_eq!(mint::Vector4 { x: 1., y: 5., z: 9., w: 13. }, c.x);
+        assert_eq!(m, Matrix4::from(c));
+
+        let m = Matrix3::from_rows([[1., 2., 3.], [4., 5., 6.], [7., 8., 9.]]);
+        assert_eq!(m, Matrix3::from(mint::ColumnMatrix3::from(m)));
+
+        let q = Quaternion::new(0.1, 0.2, 0.3, 0.9);
+        let mq: mint::Quaternion<f32> = q.into();
+        assert_eq!(0.9, mq.s);
+        assert_eq!(q, mq.into());
+    }
+}
+
+#[cfg(all(test, feature = "nalgebra-glm"))]
+mod test_glm {
+    use crate::math::{Matrix3, Matrix4, Quaternion, Vector2, Vector3, Vector4};
+    use nalgebra_glm as glm;
+
+    #[test]
+    fn test_vectors() {
+        let v = Vector3::new(1., 2., 3.);
+        let g: glm::Vec3 = v.into();
+
+        assert_eq!(glm::vec3(1., 2., 3.), g);
+        assert_eq!(v, g.into());
+        assert_eq!(Vector2::new(1., 2.), glm::vec2(1., 2.).into());
+        assert_eq!(glm::vec4(1., 2., 3., 4.), Vector4::new(1., 2., 3., 4.).into());
+        assert_eq!(glm::DVec3::new(1., 2., 3.), glm::DVec3::from(Vector3::new(1., 2., 3.).cast::<f64>()));
+    }
+
+    #[test]
+    fn test_matrices() {
+        let m = Matrix4::from_rows([[1., 2., 3., 4.], [5., 6., 7., 8.], [9., 10., 11., 12.], [13., 14., 15., 16.]]);
+        let g: glm::Mat4 = m.into();
+
+        assert_eq!(2., g[(0, 1)]);
+        assert_eq!(m, g.into());
+
+        // Products agree, so matrices from either crate can be mixed.
+        let t = glm::translation(&glm::vec3(1., 2., 3.));
+        let p = Vector3::new(1., 1., 1.);
+        assert_eq!(Matrix4::from(t).transform_point(&p), Vector3::from((t * glm::vec4(1., 1., 1., 1.)).xyz()));
+
+        let m = Matrix3::from_rows([[1., 2., 3.], [4., 5., 6.], [7., 8., 9.]]);
+        assert_eq!(m, glm::Mat3::from(m).into());
+
+        let q = Quaternion::from_axis_angle(&Vector3::new(0., 0., 1.), 0.5);
+        let gq: glm::Quat = q.into();
+        assert!((glm::quat_angle_axis(0.5, &glm::vec3(0., 0., 1.)).coords - gq.coords).norm() < 1e-6);
+        assert_eq!(q, gq.into());
+    }
+}
@@ -0,0 +1,580 @@
+use crate::math::{Scalar, Vec3, Vec4};
+use bytemuck::{Pod, Zeroable};
+use std::ops::{Index, IndexMut, Mul};
+
+/// 3x3 matrix stored column-major, `cols[c][r]`.
+///
+/// Note that a WGSL `mat3x3<f32>` uniform pads every column to 16 bytes,
+/// so upload a [`Matrix4`] when the layout has to match exactly.
+#[repr(C)]
+#[derive(PartialEq, Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Mat3<T> {
+    pub cols: [[T; 3]; 3],
+}
+
+/// 4x4 matrix stored column-major, `cols[c][r]`, which is the memory layout
+/// of a WGSL `mat4x4<f32>`.
+#[repr(C)]
+#[derive(PartialEq, Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Mat4<T> {
+    pub cols: [[T; 4]; 4],
+}
+
+pub type Matrix3 = Mat3<f32>;
+pub type Matrix3d = Mat3<f64>;
+pub type Matrix4 = Mat4<f32>;
+pub type Matrix4d = Mat4<f64>;
+
+// SAFETY: arrays of a Pod type have no padding.
+unsafe impl<T: Scalar> Zeroable for Mat3<T> {}
+unsafe impl<T: Scalar> Pod for Mat3<T> {}
+unsafe impl<T: Scalar> Zeroable for Mat4<T> {}
+unsafe impl<T: Scalar> Pod for Mat4<T> {}
+
+impl<T: Scalar> Mat3<T> {
+    pub const ZERO: Self = Self { cols: [[T::ZERO; 3]; 3] };
+
+    pub const IDENTITY: Self = Self {
+        cols: [
+            [T::ONE, T::ZERO, T::ZERO],
+            [T::ZERO, T::ONE, T::ZERO],
+            [T::ZERO, T::ZERO, T::ONE],
+        ],
+    };
+
+    /// Builds a matrix from rows, i.e. in the order it is written on paper.
+    pub fn from_rows(rows: [[T; 3]; 3]) -> Self {
+        Self { cols: rows }.transpose()
+    }
+
+    pub fn from_cols(cols: [[T; 3]; 3]) -> Self {
+        Self { cols }
+    }
+
+    pub fn from_col_vectors(u: &Vec3<T>, v: &Vec3<T>, w: &Vec3<T>) -> Self {
+        Self {
+            cols: [[u.x, u.y, u.z], [v.x, v.y, v.z], [w.x, w.y, w.z]],
+        }
+    }
+
+    pub fn row(&self, r: usize) -> Vec3<T> {
+        Vec3::new(self.cols[0][r], self.cols[1][r], self.cols[2][r])
+    }
+
+    pub fn col(&self, c: usize) -> Vec3<T> {
+        let [x, y, z] = self.cols[c];
+        Vec3::new(x, y, z)
+    }
+
+    pub fn transpose(&self) -> Self {
+        let mut m = Self::ZERO;
+        for c in 0..3 {
+            for r in 0..3 {
+                m.cols[r][c] = self.cols[c][r];
+            }
+        }
+        m
+    }
+
+    pub fn determinant(&self) -> T {
+        // triple product of the columns
+        self.col(0).dot(&self.col(1).cross(&self.col(2)))
+    }
+
+    /// Returns `None` when the matrix is singular.
+    pub fn inverse(&self) -> Option<Self> {
+        let det = self.determinant();
+        if det == T::ZERO {
+            return None;
+        }
+
+        // the rows of the inverse are the cross products of the columns
+        let (a, b, c) = (self.col(0), self.col(1), self.col(2));
+        let r0 = &b.cross(&c) / det;
+        let r1 = &c.cross(&a) / det;
+        let r2 = &a.cross(&b) / det;
+
+        Some(Self::from_col_vectors(&r0, &r1, &r2).transpose())
+    }
+
+    /// Converts the entries to another precision, rounding when narrowing.
+    pub fn cast<U: Scalar>(&self) -> Mat3<U> {
+        Mat3 {
+            cols: self.cols.map(|col| col.map(|v| U::from_f64(v.to_f64()))),
+        }
+    }
+}
+
+impl<T: Scalar> Mat4<T> {
+    pub const ZERO: Self = Self { cols: [[T::ZERO; 4]; 4] };
+
+    pub const IDENTITY: Self = Self {
+        cols: [
+            [T::ONE, T::ZERO, T::ZERO, T::ZERO],
+            [T::ZERO, T::ONE, T::ZERO, T::ZERO],
+            [T::ZERO, T::ZERO, T::ONE, T::ZERO],
+            [T::ZERO, T::ZERO, T::ZERO, T::ONE],
+        ],
+    };
+
+    /// Builds a matrix from rows, i.e. in the order it is written on paper.
+    pub fn from_rows(rows: [[T; 4]; 4]) -> Self {
+        Self { cols: rows }.transpose()
+    }
+
+    pub fn from_cols(cols: [[T; 4]; 4]) -> Self {
+        Self { cols }
+    }
+
+    pub fn row(&self, r: usize) -> [T; 4] {
+        [self.cols[0][r], self.cols[1][r], self.cols[2][r], self.cols[3][r]]
+    }
+
+    pub fn col(&self, c: usize) -> [T; 4] {
+        self.cols[c]
+    }
+
+    pub fn transpose(&self) -> Self {
+        let mut m = Self::ZERO;
+        for c in 0..4 {
+            for r in 0..4 {
+                m.cols[r][c] = self.cols[c][r];
+            }
+        }
+        m
+    }
+
+    /// The upper-left 3x3 block, i.e. the linear part of an affine transform.
+    pub fn to_matrix3(&self) -> Mat3<T> {
+        let mut m = Mat3::ZERO;
+        for c in 0..3 {
+            for r in 0..3 {
+                m.cols[c][r] = self.cols[c][r];
+            }
+        }
+        m
+    }
+
+    pub fn determinant(&self) -> T {
+        let (s, c) = self.minors();
+        s[0] * c[5] - s[1] * c[4] + s[2] * c[3] + s[3] * c[2] - s[4] * c[1] + s[5] * c[0]
+    }
+
+    /// Returns `None` when the matrix is singular.
+    pub fn inverse(&self) -> Option<Self> {
+        let (s, c) = self.minors();
+        let det = s[0] * c[5] - s[1] * c[4] + s[2] * c[3] + s[3] * c[2] - s[4] * c[1] + s[5] * c[0];
+        if det == T::ZERO {
+            return None;
+        }
+
+        let m = |r: usize, c: usize| self.cols[c][r];
+        let adjugate = [
+            [
+                m(1, 1) * c[5] - m(1, 2) * c[4] + m(1, 3) * c[3],
+                -m(1, 0) * c[5] + m(1, 2) * c[2] - m(1, 3) * c[1],
+                m(1, 0) * c[4] - m(1, 1) * c[2] + m(1, 3) * c[0],
+                -m(1, 0) * c[3] + m(1, 1) * c[1] - m(1, 2) * c[0],
+            ],
+            [
+                -m(0, 1) * c[5] + m(0, 2) * c[4] - m(0, 3) * c[3],
+                m(0, 0) * c[5] - m(0, 2) * c[2] + m(0, 3) * c[1],
+                -m(0, 0) * c[4] + m(0, 1) * c[2] - m(0, 3) * c[0],
+                m(0, 0) * c[3] - m(0, 1) * c[1] + m(0, 2) * c[0],
+            ],
+            [
+                m(3, 1) * s[5] - m(3, 2) * s[4] + m(3, 3) * s[3],
+                -m(3, 0) * s[5] + m(3, 2) * s[2] - m(3, 3) * s[1],
+                m(3, 0) * s[4] - m(3, 1) * s[2] + m(3, 3) * s[0],
+                -m(3, 0) * s[3] + m(3, 1) * s[1] - m(3, 2) * s[0],
+            ],
+            [
+                -m(2, 1) * s[5] + m(2, 2) * s[4] - m(2, 3) * s[3],
+                m(2, 0) * s[5] - m(2, 2) * s[2] + m(2, 3) * s[1],
+                -m(2, 0) * s[4] + m(2, 1) * s[2] - m(2, 3) * s[0],
+                m(2, 0) * s[3] - m(2, 1) * s[1] + m(2, 2) * s[0],
+            ],
+        ];
+
+        // adjugate[c] holds column c of the inverse
+        let mut inv = Self::from_cols(adjugate);
+        for col in inv.cols.iter_mut() {
+            for v in col.iter_mut() {
+                *v /= det;
+            }
+        }
+        Some(inv)
+    }
+
+    /// 2x2 minors of the top two rows (`s`) and bottom two rows (`c`),
+    /// shared by the Laplace expansion in `determinant` and `inverse`.
+    fn minors(&self) -> ([T; 6], [T; 6]) {
+        let m = |r: usize, c: usize| self.cols[c][r];
+        (
+            [
+                m(0, 0) * m(1, 1) - m(1, 0) * m(0, 1),
+                m(0, 0) * m(1, 2) - m(1, 0) * m(0, 2),
+                m(0, 0) * m(1, 3) - m(1, 0) * m(0, 3),
+                m(0, 1) * m(1, 2) - m(1, 1) * m(0, 2),
+                m(0, 1) * m(1, 3) - m(1, 1) * m(0, 3),
+                m(0, 2) * m(1, 3) - m(1, 2) * m(0, 3),
+            ],
+            [
+                m(2, 0) * m(3, 1) - m(3, 0) * m(2, 1),
+                m(2, 0) * m(3, 2) - m(3, 0) * m(2, 2),
+                m(2, 0) * m(3, 3) - m(3, 0) * m(2, 3),
+                m(2, 1) * m(3, 2) - m(3, 1) * m(2, 2),
+                m(2, 1) * m(3, 3) - m(3, 1) * m(2, 3),
+                m(2, 2) * m(3, 3) - m(3, 2) * m(2, 3),
+            ],
+        )
+    }
+
+    /// Multiplies a homogeneous coordinate `[x, y, z, w]`.
+    pub fn transform(&self, h: &[T; 4]) -> [T; 4] {
+        let mut out = [T::ZERO; 4];
+        for (c, col) in self.cols.iter().enumerate() {
+            for (r, v) in col.iter().enumerate() {
+                out[r] += *v * h[c];
+            }
+        }
+        out
+    }
+
+    /// Transforms `p` as a point (w = 1), dividing by the resulting w.
+    pub fn transform_point(&self, p: &Vec3<T>) -> Vec3<T> {
+        let [x, y, z, w] = self.transform(&[p.x, p.y, p.z, T::ONE]);
+        Vec3::new(x / w, y / w, z / w)
+    }
+
+    /// Transforms `v` as a direction (w = 0), so translation is ignored.
+    pub fn transform_vector(&self, v: &Vec3<T>) -> Vec3<T> {
+        let [x, y, z, _] = self.transform(&[v.x, v.y, v.z, T::ZERO]);
+        Vec3::new(x, y, z)
+    }
+
+    /// Converts the entries to another precision, rounding when narrowing.
+    pub fn cast<U: Scalar>(&self) -> Mat4<U> {
+        Mat4 {
+            cols: self.cols.map(|col| col.map(|v| U::from_f64(v.to_f64()))),
+        }
+    }
+}
+
+impl<T: Scalar> From<&Mat3<T>> for Mat4<T> {
+    fn from(m: &Mat3<T>) -> Self {
+        let mut out = Self::IDENTITY;
+        for c in 0..3 {
+            for r in 0..3 {
+                out.cols[c][r] = m.cols[c][r];
+            }
+        }
+        out
+    }
+}
+
+impl From<Matrix3> for Matrix3d {
+    fn from(m: Matrix3) -> Self {
+        m.cast()
+    }
+}
+
+impl From<Matrix4> for Matrix4d {
+    fn from(m: Matrix4) -> Self {
+        m.cast()
+    }
+}
+
+/// Indexed as `m[(row, col)]`.
+impl<T> Index<(usize, usize)> for Mat3<T> {
+    type Output = T;
+
+    fn index(&self, (r, c): (usize, usize)) -> &Self::Output {
+        &self.cols[c][r]
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Mat3<T> {
+    fn index_mut(&mut self, (r, c): (usize, usize)) -> &mut Self::Output {
+        &mut self.cols[c][r]
+    }
+}
+
+/// Indexed as `m[(row, col)]`.
+impl<T> Index<(usize, usize)> for Mat4<T> {
+    type Output = T;
+
+    fn index(&self, (r, c): (usize, usize)) -> &Self::Output {
+        &self.cols[c][r]
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Mat4<T> {
+    fn index_mut(&mut self, (r, c): (usize, usize)) -> &mut Self::Output {
+        &mut self.cols[c][r]
+    }
+}
+
+impl<T: Scalar> Mul<&Mat3<T>> for &Mat3<T> {
+    type Output = Mat3<T>;
+
+    fn mul(self, rhs: &Mat3<T>) -> Self::Output {
+        let mut m = Mat3::ZERO;
+        for c in 0..3 {
+            for r in 0..3 {
+                m.cols[c][r] = (0..3).map(|k| self.cols[k][r] * rhs.cols[c][k]).sum();
+            }
+        }
+        m
+    }
+}
+
+impl<T: Scalar> Mul<&Vec3<T>> for &Mat3<T> {
+    type Output = Vec3<T>;
+
+    fn mul(self, rhs: &Vec3<T>) -> Self::Output {
+        Vec3 {
+            x: self.row(0).dot(rhs),
+            y: self.row(1).dot(rhs),
+            z: self.row(2).dot(rhs),
+        }
+    }
+}
+
+impl<T: Scalar> Mul<T> for &Mat3<T> {
+    type Output = Mat3<T>;
+
+    fn mul(self, rhs: T) -> Self::Output {
+        let mut m = *self;
+        for col in m.cols.iter_mut() {
+            for v in col.iter_mut() {
+                *v *= rhs;
+            }
+        }
+        m
+    }
+}
+
+impl<T: Scalar> Mul<&Mat4<T>> for &Mat4<T> {
+    type Output = Mat4<T>;
+
+    fn mul(self, rhs: &Mat4<T>) -> Self::Output {
+        let mut m = Mat4::ZERO;
+        for c in 0..4 {
+            for r in 0..4 {
+                m.cols[c][r] = (0..4).map(|k| self.cols[k][r] * rhs.cols[c][k]).sum();
+            }
+        }
+        m
+    }
+}
+
+impl<T: Scalar> Mul<&Vec4<T>> for &Mat4<T> {
+    type Output = Vec4<T>;
+
+    fn mul(self, rhs: &Vec4<T>) -> Self::Output {
+        Vec4::from(self.transform(&(*rhs).into()))
+    }
+}
+
+impl<T: Scalar> Mul<T> for &Mat4<T> {
+    type Output = Mat4<T>;
+
+    fn mul(self, rhs: T) -> Self::Output {
+        let mut m = *self;
+        for col in m.cols.iter_mut() {
+            for v in col.iter_mut() {
+                *v *= rhs;
+            }
+        }
+        m
+    }
+}
+
+#[cfg(test)]
+mod test_matrix3 {
+    use super::*;
+    use crate::math::Vector3;
+
+    fn assert_near(a: &Matrix3, b: &Matrix3) {
+        for c in 0..3 {
+            for r in 0..3 {
+                assert!((a.cols[c][r] - b.cols[c][r]).abs() < 1e-5, "{:?} != {:?}", a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn test_from_rows() {
+        let m = Matrix3::from_rows([[1., 2., 3.], [4., 5., 6.], [7., 8., 9.]]);
+
+        assert_eq!(2., m[(0, 1)]);
+        assert_eq!(4., m[(1, 0)]);
+        assert_eq!(Vector3::new(4., 5., 6.), m.row(1));
+        assert_eq!(Vector3::new(3., 6., 9.), m.col(2));
+    }
+
+    #[test]
+    fn test_mul() {
+        let a = Matrix3::from_rows([[1., 2., 0.], [0., 1., 0.], [0., 0., 2.]]);
+        let b = Matrix3::from_rows([[1., 0., 0.], [3., 1., 0.], [0., 0., 1.]]);
+
+        assert_eq!(Matrix3::from_rows([[7., 2., 0.], [3., 1., 0.], [0., 0., 2.]]), &a * &b);
+        assert_eq!(a, &a * &Matrix3::IDENTITY);
+        assert_eq!(a, &Matrix3::IDENTITY * &a);
+        assert_eq!(Vector3::new(5., 2., 6.), &a * &Vector3::new(1., 2., 3.));
+    }
+
+    #[test]
+    fn test_transpose() {
+        let m = Matrix3::from_rows([[1., 2., 3.], [4., 5., 6.], [7., 8., 9.]]);
+
+        assert_eq!(Matrix3::from_cols([[1., 2., 3.], [4., 5., 6.], [7., 8., 9.]]), m.transpose());
+        assert_eq!(m, m.transpose().transpose());
+    }
+
+    #[test]
+    fn test_determinant() {
+        assert_eq!(1., Matrix3::IDENTITY.determinant());
+        assert_eq!(0., Matrix3::from_rows([[1., 2., 3.], [4., 5., 6.], [7., 8., 9.]]).determinant());
+        assert_eq!(3., Matrix3::from_rows([[2., 0., 1.], [1., 1., 0.], [0., 3., 0.]]).determinant());
+    }
+
+    #[test]
+    fn test_inverse() {
+        let m = Matrix3::from_rows([[2., 0., 1.], [1., 1., 0.], [0., 3., 0.]]);
+        let inv = m.inverse().unwrap();
+
+        assert_near(&Matrix3::IDENTITY, &(&m * &inv));
+        assert_near(&Matrix3::IDENTITY, &(&inv * &m));
+        assert_eq!(None, Matrix3::from_rows([[1., 2., 3.], [4., 5., 6.], [7., 8., 9.]]).inverse());
+    }
+}
+
+#[cfg(test)]
+mod test_matrix4 {
+    use super::*;
+    use crate::math::{Vector3, Vector4};
+
+    fn assert_near(a: &Matrix4, b: &Matrix4) {
+        for c in 0..4 {
+            for r in 0..4 {
+                assert!((a.cols[c][r] - b.cols[c][r]).abs() < 1e-5, "{:?} != {:?}", a, b);
+            }
+        }
+    }
+
+    fn translate(x: f32, y: f32, z: f32) -> Matrix4 {
+        Matrix4::from_rows([
+            [1., 0., 0., x],
+            [0., 1., 0., y],
+            [0., 0., 1., z],
+            [0., 0., 0., 1.],
+        ])
+    }
+
+    #[test]
+    fn test_mul() {
+        let t = translate(1., 2., 3.);
+        let s = Matrix4::from(&(&Matrix3::IDENTITY * 2.));
+
+        assert_eq!(t, &t * &Matrix4::IDENTITY);
+        assert_eq!(t, &Matrix4::IDENTITY * &t);
+        assert_eq!(translate(3., 5., 7.), &t * &translate(2., 3., 4.));
+        assert_eq!(
+            Matrix4::from_rows([
+                [2., 0., 0., 1.],
+                [0., 2., 0., 2.],
+                [0., 0., 2., 3.],
+                [0., 0., 0., 1.],
+            ]),
+            &t * &s
+        );
+    }
+
+    #[test]
+    fn test_transpose() {
+        let t = translate(1., 2., 3.);
+
+        assert_eq!([1., 2., 3., 1.], t.transpose().row(3));
+        assert_eq!(t, t.transpose().transpose());
+    }
+
+    #[test]
+    fn test_determinant() {
+        assert_eq!(1., Matrix4::IDENTITY.determinant());
+        assert_eq!(1., translate(1., 2., 3.).determinant());
+        assert_eq!(16., (&Matrix4::IDENTITY * 2.).determinant());
+        assert_eq!(
+            3.,
+            Matrix4::from(&Matrix3::from_rows([[2., 0., 1.], [1., 1., 0.], [0., 3., 0.]])).determinant()
+        );
+    }
+
+    #[test]
+    fn test_inverse() {
+        let t = translate(1., 2., 3.);
+        assert_eq!(Some(translate(-1., -2., -3.)), t.inverse());
+
+        let m = Matrix4::from_rows([
+            [2., 1., 0., 4.],
+            [0., 3., 1., -1.],
+            [1., 0., 2., 2.],
+            [0., 1., 0., 1.],
+        ]);
+        let inv = m.inverse().unwrap();
+        assert_near(&Matrix4::IDENTITY, &(&m * &inv));
+        assert_near(&Matrix4::IDENTITY, &(&inv * &m));
+        assert_eq!(None, Matrix4::ZERO.inverse());
+    }
+
+    #[test]
+    fn test_transform() {
+        let t = translate(1., 2., 3.);
+        let p = Vector3::new(1., 1., 1.);
+
+        assert_eq!(Vector3::new(2., 3., 4.), t.transform_point(&p));
+        assert_eq!(p, t.transform_vector(&p));
+        assert_eq!([2., 3., 4., 1.], t.transform(&[1., 1., 1., 1.]));
+        assert_eq!(Vector4::new(2., 3., 4., 1.), &t * &p.extend(1.));
+        assert_eq!(p.extend(0.), &t * &p.extend(0.));
+
+        // w != 1 is divided out
+        let mut w2 = Matrix4::IDENTITY;
+        w2[(3, 3)] = 2.;
+        assert_eq!(Vector3::new(0.5, 0.5, 0.5), w2.transform_point(&p));
+    }
+
+    #[test]
+    fn test_to_matrix3() {
+        let m3 = Matrix3::from_rows([[1., 2., 3.], [4., 5., 6.], [7., 8., 9.]]);
+        let mut m4 = Matrix4::from(&m3);
+        m4[(0, 3)] = 5.;
+
+        assert_eq!(m3, m4.to_matrix3());
+        assert_eq!(5., m4.col(3)[0]);
+    }
+
+    #[test]
+    fn test_f64() {
+        let m = Matrix4::from_rows([
+            [2., 1., 0., 4.],
+            [0., 3., 1., -1.],
+            [1., 0., 2., 2.],
+            [0., 1., 0., 1.],
+        ]);
+        let md = Matrix4d::from(m);
+
+        assert_eq!(m, md.cast());
+        assert_eq!(m.determinant() as f64, md.determinant());
+
+        let id = &md * &md.inverse().unwrap();
+        for c in 0..4 {
+            for r in 0..4 {
+                assert!((Matrix4d::IDENTITY[(r, c)] - id[(r, c)]).abs() < 1e-12);
+            }
+        }
+    }
+}
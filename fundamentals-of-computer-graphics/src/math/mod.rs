@@ -0,0 +1,619 @@
+use bytemuck::{Pod, Zeroable};
+use std::ops::{Add, Div, Index, Mul, Neg, Sub};
+
+mod aabb;
+mod frustum;
+mod interop;
+mod matrix;
+mod predicates;
+mod quaternion;
+mod scalar;
+mod simd;
+mod transform;
+mod triangle;
+mod vector;
+mod viewing;
+
+pub use aabb::{Aabb, BoundingBox, BoundingBoxd};
+pub use frustum::{Containment, Frustum};
+pub use matrix::{Mat3, Mat4, Matrix3, Matrix3d, Matrix4, Matrix4d};
+pub use predicates::{incircle, orient2d, orient3d};
+pub use quaternion::{Quat, Quaternion, Quaterniond};
+pub use scalar::Scalar;
+pub use simd::{F32x4, F32x8, F32xN, Mask4, Mask8, MaskN, Vec3xN, Vector3x4, Vector3x8};
+pub use transform::{Transform, Transformd, Xform};
+pub use triangle::{Tri, Triangle, Triangled};
+pub use vector::{vec2, vec4, Vec2, Vec4, Vector2, Vector2d, Vector4, Vector4d};
+
+#[repr(C)]
+#[derive(PartialOrd, PartialEq, Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Vec3<T> {
+    pub x: T,
+    pub y: T,
+    pub z: T,
+}
+
+/// Single precision, the layout of a WGSL `vec3f`.
+pub type Vector3 = Vec3<f32>;
+
+/// Double precision, for computations prone to cancellation.
+pub type Vector3d = Vec3<f64>;
+
+// SAFETY: repr(C) with three fields of the same Pod type has no padding.
+unsafe impl<T: Scalar> Zeroable for Vec3<T> {}
+unsafe impl<T: Scalar> Pod for Vec3<T> {}
+
+impl<T: Scalar> Vec3<T> {
+    pub fn new(x: T, y: T, z: T) -> Self {
+        Self {x, y, z}
+    }
+
+    /// Converts the components to another precision, rounding when
+    /// narrowing. Widening is also available losslessly through `From`.
+    pub fn cast<U: Scalar>(&self) -> Vec3<U> {
+        Vec3::new(U::from_f64(self.x.to_f64()), U::from_f64(self.y.to_f64()), U::from_f64(self.z.to_f64()))
+    }
+}
+
+impl From<Vector3> for Vector3d {
+    fn from(v: Vector3) -> Self {
+        v.cast()
+    }
+}
+
+pub fn vec3(x: f32, y: f32, z: f32) -> Vector3 {
+    Vector3::new(x, y, z)
+}
+
+impl<T: Scalar> Div<T> for &Vec3<T> {
+    type Output = Vec3<T>;
+
+    fn div(self, rhs: T) -> Self::Output {
+        Self::Output {
+            x: self.x / rhs,
+            y: self.y / rhs,
+            z: self.z / rhs,
+        }
+    }
+}
+
+impl<T: Scalar> Vec3<T> {
+    pub fn normalize(&self) -> Self {
+        self / self.norm()
+    }
+
+    pub fn norm(&self) -> T {
+        self.dot(self).sqrt()
+    }
+
+    pub fn dot(&self, rhs: &Self) -> T {
+        self.x * rhs.x + self.y * rhs.y + self.z * rhs.z
+    }
+
+    pub fn cross(&self, rhs: &Self) -> Self {
+        Self {
+            x: self.y * rhs.z - self.z * rhs.y,
+            y: self.z * rhs.x - self.x * rhs.z,
+            z: self.x * rhs.y - self.y * rhs.x,
+        }
+    }
+
+    /// Component-wise minimum.
+    pub fn min(&self, rhs: &Self) -> Self {
+        Self::new(self.x.min(rhs.x), self.y.min(rhs.y), self.z.min(rhs.z))
+    }
+
+    /// Component-wise maximum.
+    pub fn max(&self, rhs: &Self) -> Self {
+        Self::new(self.x.max(rhs.x), self.y.max(rhs.y), self.z.max(rhs.z))
+    }
+}
+
+impl<T> Index<usize> for Vec3<T> {
+    type Output = T;
+
+    fn index(&self, i: usize) -> &Self::Output {
+        match i {
+            0 => &self.x,
+            1 => &self.y,
+            2 => &self.z,
+            _ => panic!("Vec3 index out of range: {}", i),
+        }
+    }
+}
+
+/// `scalar * vector` has to be spelled out per type, a foreign type cannot
+/// be generic on the left-hand side.
+macro_rules! impl_scalar_mul_vec3 {
+    ($t:ident) => {
+        impl Mul<&Vec3<$t>> for $t {
+            type Output = Vec3<$t>;
+
+            fn mul(self, rhs: &Vec3<$t>) -> Self::Output {
+                Self::Output {
+                    x: self * rhs.x,
+                    y: self * rhs.y,
+                    z: self * rhs.z,
+                }
+            }
+        }
+    };
+}
+
+impl_scalar_mul_vec3!(f32);
+impl_scalar_mul_vec3!(f64);
+
+impl<T: Scalar> Mul<T> for &Vec3<T> {
+    type Output = Vec3<T>;
+
+    fn mul(self, rhs: T) -> Self::Output {
+        Self::Output {
+            x: self.x * rhs,
+            y: self.y * rhs,
+            z: self.z * rhs,
+        }
+    }
+}
+
+impl<T: Scalar> Neg for &Vec3<T> {
+    type Output = Vec3<T>;
+
+    fn neg(self) -> Self::Output {
+        Self::Output {
+            x: -self.x,
+            y: -self.y,
+            z: -self.z,
+        }
+    }
+}
+
+impl<T: Scalar> Add<&Vec3<T>> for &Vec3<T> {
+    type Output = Vec3<T>;
+
+    fn add(self, rhs: &Vec3<T>) -> Self::Output {
+        Vec3 {
+            x: self.x + rhs.x,
+            y: self.y + rhs.y,
+            z: self.z + rhs.z,
+        }
+    }
+}
+
+impl<T: Scalar> Sub<&Vec3<T>> for &Vec3<T> {
+    type Output = Vec3<T>;
+
+    fn sub(self, rhs: &Vec3<T>) -> Self::Output {
+        Self::Output {
+            x: self.x - rhs.x,
+            y: self.y - rhs.y,
+            z: self.z - rhs.z,
+        }
+    }
+}
+
+impl<T: Scalar> Mul<&Vec3<T>> for &Vec3<T> {
+    type Output = T;
+
+    fn mul(self, rhs: &Vec3<T>) -> Self::Output {
+        self.dot(rhs)
+    }
+}
+
+#[cfg(test)]
+mod test_vector3 {
+    use crate::math::Vector3;
+
+    #[test]
+    fn test_add() {
+        let a = Vector3 {
+            x: 1.,
+            y: 2.,
+            z: 3.,
+        };
+        let b = Vector3 {
+            x: 1.,
+            y: 2.,
+            z: 3.,
+        };
+
+        assert_eq!(
+            Vector3 {
+                x: 2.,
+                y: 4.,
+                z: 6.
+            },
+            &a + &b
+        );
+        assert_eq!(
+            Vector3 {
+                x: 2.,
+                y: 4.,
+                z: 6.
+            },
+            &b + &a
+        );
+        assert_eq!(
+            Vector3 {
+                x: 2.,
+                y: 4.,
+                z: 6.
+            },
+            &b + &a
+        );
+    }
+
+    #[test]
+    fn test_sub() {
+        let a = Vector3 {
+            x: 1.,
+            y: 2.,
+            z: 3.,
+        };
+        let b = Vector3 {
+            x: 3.,
+            y: 2.,
+            z: 1.,
+        };
+        let b = Vector3 {
+            x: 3.,
+            y: 2.,
+            z: 1.,
+        };
+
+        assert_eq!(
+            Vector3 {
+                x: -2.,
+                y: 0.,
+                z: 2.
+            },
+            &a - &b
+        );
+        assert_eq!(
+            Vector3 {
+                x: -2.,
+                y: 0.,
+                z: 2.
+            },
+            &a - &b
+        );
+        assert_eq!(
+            Vector3 {
+                x: 2.,
+                y: 0.,
+                z: -2.
+            },
+            &b - &a
+        );
+        assert_eq!(
+            Vector3 {
+                x: 2.,
+                y: 0.,
+                z: -2.
+            },
+            &b - &a
+        );
+    }
+
+    #[test]
+    fn test_dot() {
+        let a = Vector3 {
+            x: 1.,
+            y: 2.,
+            z: 3.,
+        };
+        let b = Vector3 {
+            x: 1.,
+            y: 2.,
+            z: 3.,
+        };
+
+        assert_eq!(14., a.dot(&b));
+        assert_eq!(14., b.dot(&a));
+        assert_eq!(14., &a * &b);
+        assert_eq!(14., &b * &a);
+    }
+
+    #[test]
+    fn test_cross() {
+        let x = super::X;
+        let y = super::Y;
+        let z = super::Z;
+
+        assert_eq!(x, y.cross(&z));
+        assert_eq!(y, z.cross(&x));
+        assert_eq!(z, x.cross(&y));
+
+        assert_eq!(-&x, z.cross(&y));
+        assert_eq!(-&y, x.cross(&z));
+        assert_eq!(-&z, y.cross(&x));
+    }
+
+    #[test]
+    fn test_norm() {
+        let a = Vector3::new(1., 0., 0.);
+        let b = Vector3::new(2., 0., 0.);
+        assert_eq!((&b-&a).norm(), 1.);
+    }
+
+    #[test]
+    fn test_precision() {
+        use crate::math::Vector3d;
+
+        let a = Vector3::new(0.1, 0.2, 0.3);
+        assert_eq!(Vector3d::new(0.1f32 as f64, 0.2f32 as f64, 0.3f32 as f64), Vector3d::from(a));
+        assert_eq!(a, Vector3d::from(a).cast());
+
+        // |o - c|² - r² for a point just above the ray tracer's big floor
+        // sphere, which f32 rounds onto the surface
+        let o = Vector3d::new(0., -0.9999, 0.);
+        let c = Vector3d::new(0., -5001., 0.);
+        let d = &o - &c;
+        assert!(d.dot(&d) - 5000. * 5000. > 0.5);
+
+        let o = o.cast::<f32>();
+        let c = c.cast::<f32>();
+        let d = &o - &c;
+        assert_eq!(0., d.dot(&d) - 5000. * 5000.);
+    }
+}
+
+#[cfg(test)]
+const X: Vector3 = Vector3 {
+    x: 1.,
+    y: 0.,
+    z: 0.,
+};
+#[cfg(test)]
+const Y: Vector3 = Vector3 {
+    x: 0.,
+    y: 1.,
+    z: 0.,
+};
+#[cfg(test)]
+const Z: Vector3 = Vector3 {
+    x: 0.,
+    y: 0.,
+    z: 1.,
+};
+
+/// Right-handed orthonormal basis, `u × v = w`.
+#[derive(PartialEq, Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Onb<T> {
+    pub u: Vec3<T>,
+    pub v: Vec3<T>,
+    pub w: Vec3<T>,
+}
+
+pub type OrthonormalBasis = Onb<f32>;
+pub type OrthonormalBasisd = Onb<f64>;
+
+impl<T: Scalar> Onb<T> {
+    /// The canonical basis x, y, z.
+    pub const CANONICAL: Self = Self {
+        u: Vec3 { x: T::ONE, y: T::ZERO, z: T::ZERO },
+        v: Vec3 { x: T::ZERO, y: T::ONE, z: T::ZERO },
+        w: Vec3 { x: T::ZERO, y: T::ZERO, z: T::ONE },
+    };
+
+    /// Basis whose w points along `a`; u and v are arbitrary (FCG 2.4.6).
+    pub fn from_single_vector(a: &Vec3<T>) -> Self {
+        let w = a.normalize();
+
+        let t = {
+            let (mut x, mut y, mut z) = (w.x.abs(), w.y.abs(), w.z.abs());
+            if x > y {
+                if y > z {
+                    z = T::ONE;
+                } else {
+                    y = T::ONE
+                }
+            } else {
+                if x > z { z = T::ONE } else { x = T::ONE }
+            }
+
+            Vec3 { x, y, z }
+        };
+
+        let u = { t.cross(&w).normalize() };
+
+        let v = w.cross(&u);
+
+        Self { u, v, w }
+    }
+
+    /// Basis whose w points along `a` and whose v lies in the plane of `a`
+    /// and `b` (FCG 2.4.7), e.g. a camera frame from -gaze and up. If `b` is
+    /// parallel to `a` there is no such plane, and u and v are arbitrary as
+    /// for `from_single_vector`.
+    pub fn from_two_vectors(a: &Vec3<T>, b: &Vec3<T>) -> Self {
+        let w = a.normalize();
+        let u = b.cross(&w);
+        if u.norm() == T::ZERO {
+            return Self::from_single_vector(a);
+        }
+        let u = u.normalize();
+        let v = w.cross(&u);
+
+        Self { u, v, w }
+    }
+
+    /// Shading frame with w along the unit normal `n`, branch-free and
+    /// continuous everywhere but n.z = 0 (Duff et al. 2017).
+    pub fn from_normal(n: &Vec3<T>) -> Self {
+        let sign = T::ONE.copysign(n.z);
+        let a = -T::ONE / (sign + n.z);
+        let b = n.x * n.y * a;
+
+        Self {
+            u: Vec3::new(T::ONE + sign * n.x * n.x * a, sign * b, -sign * n.x),
+            v: Vec3::new(b, sign + n.y * n.y * a, -n.y),
+            w: *n,
+        }
+    }
+
+    /// Coordinates of the world-space vector `a` in this basis.
+    pub fn to_local(&self, a: &Vec3<T>) -> Vec3<T> {
+        Vec3::new(a.dot(&self.u), a.dot(&self.v), a.dot(&self.w))
+    }
+
+    /// World-space vector for the coordinates `a` in this basis.
+    pub fn to_world(&self, a: &Vec3<T>) -> Vec3<T> {
+        &(&(&self.u * a.x) + &(&self.v * a.y)) + &(&self.w * a.z)
+    }
+
+    /// Matrix with u, v, w as columns, i.e. the local-to-world rotation.
+    pub fn to_matrix3(&self) -> Mat3<T> {
+        Mat3::from_col_vectors(&self.u, &self.v, &self.w)
+    }
+
+    /// Converts the axes to another precision, rounding when narrowing.
+    pub fn cast<U: Scalar>(&self) -> Onb<U> {
+        Onb {
+            u: self.u.cast(),
+            v: self.v.cast(),
+            w: self.w.cast(),
+        }
+    }
+}
+
+impl From<OrthonormalBasis> for OrthonormalBasisd {
+    fn from(b: OrthonormalBasis) -> Self {
+        b.cast()
+    }
+}
+
+#[cfg(test)]
+mod test_basis {
+    use super::*;
+
+    #[test]
+    fn test_from_single_vector() {
+        let basis = OrthonormalBasis::from_single_vector(&Vector3 {
+            x: 2.5,
+            y: 1.5,
+            z: 0.5,
+        });
+
+        let (u, v, w) = (basis.u, basis.v, basis.w);
+
+        assert_eq!(u.norm().round(), 1.);
+        assert_eq!(v.norm().round(), 1.);
+        assert_eq!(w.norm().round(), 1.);
+    }
+
+    fn assert_near(a: &Vector3, b: &Vector3) {
+        assert!((a - b).norm() < 1e-5, "{:?} != {:?}", a, b);
+    }
+
+    fn assert_orthonormal(basis: &OrthonormalBasis) {
+        let (u, v, w) = (basis.u, basis.v, basis.w);
+
+        assert!((u.norm() - 1.).abs() < 1e-5);
+        assert!((v.norm() - 1.).abs() < 1e-5);
+        assert!((w.norm() - 1.).abs() < 1e-5);
+        assert!(u.dot(&v).abs() < 1e-5);
+        assert!(v.dot(&w).abs() < 1e-5);
+        assert!(w.dot(&u).abs() < 1e-5);
+        assert_near(&w, &u.cross(&v));
+    }
+
+    #[test]
+    fn test_from_two_vectors() {
+        let a = Vector3::new(0., 0., 2.);
+        let b = Vector3::new(0., 3., 1.);
+        let basis = OrthonormalBasis::from_two_vectors(&a, &b);
+
+        assert_orthonormal(&basis);
+        assert_near(&a.normalize(), &basis.w);
+        assert_near(&Y, &basis.v);
+        assert_near(&X, &basis.u);
+        assert_eq!(OrthonormalBasis::CANONICAL, OrthonormalBasis::from_two_vectors(&Z, &Y));
+
+        // looking straight up or down leaves no plane for v
+        for b in [Vector3::new(0., 0., 4.), Vector3::new(0., 0., -1.), Vector3::new(0., 0., 0.)] {
+            let basis = OrthonormalBasis::from_two_vectors(&a, &b);
+            assert_orthonormal(&basis);
+            assert_near(&a.normalize(), &basis.w);
+        }
+    }
+
+    #[test]
+    fn test_from_normal() {
+        for n in [
+            Vector3::new(2.5, 1.5, 0.5),
+            Vector3::new(0., 0., 1.),
+            Vector3::new(0., 0., -1.),
+            Vector3::new(1., 0., 0.),
+            Vector3::new(-0.3, 0.8, -0.2),
+        ] {
+            let n = n.normalize();
+            let basis = OrthonormalBasis::from_normal(&n);
+
+            assert_orthonormal(&basis);
+            assert_eq!(n, basis.w);
+        }
+        assert_orthonormal(&OrthonormalBasis::from_single_vector(&Vector3::new(-0.3, 0.8, -0.2)));
+    }
+
+    #[test]
+    fn test_to_local_to_world() {
+        let basis = OrthonormalBasis::from_two_vectors(&Vector3::new(1., 2., 3.), &Vector3::new(0., 1., 0.));
+        let a = Vector3::new(0.5, -1., 2.);
+
+        assert_near(&a, &basis.to_world(&basis.to_local(&a)));
+        assert_near(&a, &basis.to_local(&basis.to_world(&a)));
+        assert_near(&basis.w, &basis.to_world(&Z));
+        assert_near(&Z, &basis.to_local(&basis.w));
+        assert_near(&basis.to_world(&a), &(&basis.to_matrix3() * &a));
+    }
+
+    #[test]
+    fn test_precision() {
+        let basis = OrthonormalBasis::from_two_vectors(&Vector3::new(1., 2., 3.), &Vector3::new(0., 1., 0.));
+        assert_eq!(basis, OrthonormalBasisd::from(basis).cast());
+
+        // the frame of a nearly grazing direction stays orthogonal in f64
+        let a = Vector3d::new(1., 1e-9, 0.);
+        let basis = OrthonormalBasisd::from_two_vectors(&a, &Vector3d::new(1., 1e-9, 1e-9));
+        assert!(basis.u.dot(&basis.v).abs() < 1e-12 && basis.v.dot(&basis.w).abs() < 1e-12);
+        assert_eq!(OrthonormalBasisd::CANONICAL, OrthonormalBasis::CANONICAL.into());
+    }
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod test_serde {
+    use super::*;
+    use serde::{de::DeserializeOwned, Serialize};
+    use std::fmt::Debug;
+
+    fn round_trip<T: Serialize + DeserializeOwned + PartialEq + Debug>(value: &T) -> String {
+        let json = serde_json::to_string(value).unwrap();
+        assert_eq!(*value, serde_json::from_str::<T>(&json).unwrap());
+        json
+    }
+
+    #[test]
+    fn test_round_trip() {
+        assert_eq!(r#"{"x":1.0,"y":2.0,"z":3.0}"#, round_trip(&Vector3::new(1., 2., 3.)));
+        round_trip(&Vector3d::new(0.1, 0.2, 0.3));
+        round_trip(&vec2(1., 2.));
+        round_trip(&vec4(1., 2., 3., 4.));
+        round_trip(&Matrix3::from_rows([[1., 2., 3.], [4., 5., 6.], [7., 8., 10.]]));
+        round_trip(&Matrix4::IDENTITY);
+        round_trip(&Quaternion::from_axis_angle(&Vector3::new(0., 1., 0.), 0.5));
+        round_trip(&OrthonormalBasis::CANONICAL);
+        round_trip(&BoundingBox::new(&Vector3::new(-1., 0., 0.), &Vector3::new(1., 2., 3.)));
+    }
+
+    #[test]
+    fn test_transform() {
+        let t = Transform::translate(&Vector3::new(1., 2., 3.));
+        let json = round_trip(&t);
+
+        // Only the matrix is stored.
+        assert_eq!(json, serde_json::to_string(t.matrix()).unwrap());
+        assert!(serde_json::from_str::<Transform>(&serde_json::to_string(&Matrix4::ZERO).unwrap()).is_err());
+    }
+}
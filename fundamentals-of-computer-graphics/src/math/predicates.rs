@@ -0,0 +1,253 @@
+//! Adaptive-precision geometric predicates after Shewchuk, "Adaptive
+//! Precision Floating-Point Arithmetic and Fast Robust Geometric Predicates"
+//! (1997).
+//!
+//! Each predicate first evaluates its determinant in plain `f64` and returns
+//! it when it is larger than the rounding error bound. Otherwise the
+//! determinant is recomputed exactly with floating-point expansions. Either
+//! way the sign of the result is exact, the magnitude is only approximate.
+//! `f32` and `f64` inputs are both widened to `f64` without rounding.
+
+use crate::math::{Scalar, Vec2, Vec3};
+
+const EPSILON: f64 = f64::EPSILON / 2.;
+const CCW_ERRBOUND: f64 = (3. + 16. * EPSILON) * EPSILON;
+const O3D_ERRBOUND: f64 = (7. + 56. * EPSILON) * EPSILON;
+const ICC_ERRBOUND: f64 = (10. + 96. * EPSILON) * EPSILON;
+
+/// Positive when `a`, `b` and `c` are in counter-clockwise order, negative
+/// when clockwise and zero when they are collinear.
+pub fn orient2d<T: Scalar>(a: &Vec2<T>, b: &Vec2<T>, c: &Vec2<T>) -> f64 {
+    let (ax, ay, bx, by, cx, cy) = (a.x.to_f64(), a.y.to_f64(), b.x.to_f64(), b.y.to_f64(), c.x.to_f64(), c.y.to_f64());
+
+    let left = (ax - cx) * (by - cy);
+    let right = (ay - cy) * (bx - cx);
+    let det = left - right;
+    let bound = CCW_ERRBOUND * (left.abs() + right.abs());
+    if det.abs() > bound || (det == 0. && bound == 0.) {
+        return det;
+    }
+
+    let (acx, bcx, acy, bcy) = (diff(ax, cx), diff(bx, cx), diff(ay, cy), diff(by, cy));
+    estimate(&sub(&product(&acx, &bcy), &product(&acy, &bcx)))
+}
+
+/// Positive when `d` lies below the plane through `a`, `b` and `c`, where
+/// below means that `a`, `b` and `c` appear counter-clockwise when seen from
+/// above. Negative when `d` is above and zero when the points are coplanar.
+pub fn orient3d<T: Scalar>(a: &Vec3<T>, b: &Vec3<T>, c: &Vec3<T>, d: &Vec3<T>) -> f64 {
+    let [a, b, c, d] = [a, b, c, d].map(|p| [p.x.to_f64(), p.y.to_f64(), p.z.to_f64()]);
+    let [ad, bd, cd] = [a, b, c].map(|p| [p[0] - d[0], p[1] - d[1], p[2] - d[2]]);
+
+    let (bdx_cdy, cdx_bdy) = (bd[0] * cd[1], cd[0] * bd[1]);
+    let (cdx_ady, adx_cdy) = (cd[0] * ad[1], ad[0] * cd[1]);
+    let (adx_bdy, bdx_ady) = (ad[0] * bd[1], bd[0] * ad[1]);
+
+    let det = ad[2] * (bdx_cdy - cdx_bdy) + bd[2] * (cdx_ady - adx_cdy) + cd[2] * (adx_bdy - bdx_ady);
+    let permanent = (bdx_cdy.abs() + cdx_bdy.abs()) * ad[2].abs()
+        + (cdx_ady.abs() + adx_cdy.abs()) * bd[2].abs()
+        + (adx_bdy.abs() + bdx_ady.abs()) * cd[2].abs();
+    let bound = O3D_ERRBOUND * permanent;
+    if det.abs() > bound || (det == 0. && bound == 0.) {
+        return det;
+    }
+
+    let [ad, bd, cd] = [a, b, c].map(|p| [0, 1, 2].map(|i| diff(p[i], d[i])));
+    let minor = |p: &[Vec<f64>; 3], q: &[Vec<f64>; 3]| sub(&product(&p[0], &q[1]), &product(&q[0], &p[1]));
+    estimate(&sum(
+        &sum(&product(&ad[2], &minor(&bd, &cd)), &product(&bd[2], &minor(&cd, &ad))),
+        &product(&cd[2], &minor(&ad, &bd)),
+    ))
+}
+
+/// Positive when `d` lies inside the circle through `a`, `b` and `c`,
+/// negative when outside and zero when the four points are cocircular.
+/// `a`, `b` and `c` must be in counter-clockwise order, otherwise the sign
+/// is reversed.
+pub fn incircle<T: Scalar>(a: &Vec2<T>, b: &Vec2<T>, c: &Vec2<T>, d: &Vec2<T>) -> f64 {
+    let [a, b, c, d] = [a, b, c, d].map(|p| [p.x.to_f64(), p.y.to_f64()]);
+    let [ad, bd, cd] = [a, b, c].map(|p| [p[0] - d[0], p[1] - d[1]]);
+
+    let (bdx_cdy, cdx_bdy) = (bd[0] * cd[1], cd[0] * bd[1]);
+    let (cdx_ady, adx_cdy) = (cd[0] * ad[1], ad[0] * cd[1]);
+    let (adx_bdy, bdx_ady) = (ad[0] * bd[1], bd[0] * ad[1]);
+    let [alift, blift, clift] = [ad, bd, cd].map(|p| p[0] * p[0] + p[1] * p[1]);
+
+    let det = alift * (bdx_cdy - cdx_bdy) + blift * (cdx_ady - adx_cdy) + clift * (adx_bdy - bdx_ady);
+    let permanent = (bdx_cdy.abs() + cdx_bdy.abs()) * alift
+        + (cdx_ady.abs() + adx_cdy.abs()) * blift
+        + (adx_bdy.abs() + bdx_ady.abs()) * clift;
+    let bound = ICC_ERRBOUND * permanent;
+    if det.abs() > bound || (det == 0. && bound == 0.) {
+        return det;
+    }
+
+    let [ad, bd, cd] = [a, b, c].map(|p| [0, 1].map(|i| diff(p[i], d[i])));
+    let lift = |p: &[Vec<f64>; 2]| sum(&product(&p[0], &p[0]), &product(&p[1], &p[1]));
+    let minor = |p: &[Vec<f64>; 2], q: &[Vec<f64>; 2]| sub(&product(&p[0], &q[1]), &product(&q[0], &p[1]));
+    estimate(&sum(
+        &sum(&product(&lift(&ad), &minor(&bd, &cd)), &product(&lift(&bd), &minor(&cd, &ad))),
+        &product(&lift(&cd), &minor(&ad, &bd)),
+    ))
+}
+
+// Expansions are sums of non-overlapping f64 components in increasing order
+// of magnitude, with zeros removed. Their sign is the sign of the last one.
+
+/// `a + b` as a rounded sum and its exact rounding error.
+fn two_sum(a: f64, b: f64) -> (f64, f64) {
+    let x = a + b;
+    let b_virtual = x - a;
+    let a_virtual = x - b_virtual;
+    (x, (a - a_virtual) + (b - b_virtual))
+}
+
+/// `a * b` as a rounded product and its exact rounding error.
+fn two_product(a: f64, b: f64) -> (f64, f64) {
+    let x = a * b;
+    (x, a.mul_add(b, -x))
+}
+
+/// Adds a single component to an expansion.
+fn grow(e: &[f64], b: f64) -> Vec<f64> {
+    let mut h = Vec::with_capacity(e.len() + 1);
+    let mut q = b;
+    for &ei in e {
+        let (sum, err) = two_sum(q, ei);
+        if err != 0. {
+            h.push(err);
+        }
+        q = sum;
+    }
+    if q != 0. {
+        h.push(q);
+    }
+    h
+}
+
+fn diff(a: f64, b: f64) -> Vec<f64> {
+    let (x, err) = two_sum(a, -b);
+    grow(&grow(&[], err), x)
+}
+
+fn sum(e: &[f64], f: &[f64]) -> Vec<f64> {
+    f.iter().fold(e.to_vec(), |h, &fi| grow(&h, fi))
+}
+
+fn sub(e: &[f64], f: &[f64]) -> Vec<f64> {
+    f.iter().fold(e.to_vec(), |h, &fi| grow(&h, -fi))
+}
+
+fn product(e: &[f64], f: &[f64]) -> Vec<f64> {
+    let mut h = Vec::new();
+    for &ei in e {
+        for &fi in f {
+            let (x, err) = two_product(ei, fi);
+            h = grow(&grow(&h, err), x);
+        }
+    }
+    h
+}
+
+/// Approximate value of an expansion with the exact sign.
+fn estimate(e: &[f64]) -> f64 {
+    match e.last() {
+        Some(&largest) => {
+            let approx: f64 = e.iter().sum();
+            if approx.signum() == largest.signum() { approx } else { largest }
+        }
+        None => 0.,
+    }
+}
+
+#[cfg(test)]
+mod test_predicates {
+    use super::*;
+    use crate::math::{vec2, Vector2d, Vector3, Vector3d};
+
+    #[test]
+    fn test_orient2d() {
+        let (a, b) = (vec2(0., 0.), vec2(1., 0.));
+
+        assert!(orient2d(&a, &b, &vec2(0., 1.)) > 0.);
+        assert!(orient2d(&a, &b, &vec2(0., -1.)) < 0.);
+        assert_eq!(0., orient2d(&a, &b, &vec2(7., 0.)));
+        assert_eq!(0., orient2d(&vec2(0.1, 0.1), &vec2(0.3, 0.3), &vec2(0.7, 0.7)));
+    }
+
+    #[test]
+    fn test_orient2d_near_collinear() {
+        // Kettner et al., "Classroom examples of robustness problems": p is
+        // perturbed off the line y = x by a few ulps, the sign of the exact
+        // determinant is the sign of (j - i). Plain evaluation gets a large
+        // part of this grid wrong.
+        let (q, r) = (Vector2d::new(12., 12.), Vector2d::new(24., 24.));
+        let ulp = 0.5f64.powi(53);
+
+        for i in 0..64i32 {
+            for j in 0..64 {
+                let p = Vector2d::new(0.5 + i as f64 * ulp, 0.5 + j as f64 * ulp);
+                let expected = (j - i).signum() as f64;
+                let det = orient2d(&p, &q, &r);
+
+                assert_eq!(expected == 0., det == 0., "i:{i}, j:{j}");
+                if det != 0. {
+                    assert_eq!(expected, det.signum(), "i:{i}, j:{j}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_orient3d() {
+        let (a, b, c) = (Vector3::new(0., 0., 0.), Vector3::new(1., 0., 0.), Vector3::new(0., 1., 0.));
+
+        assert!(orient3d(&a, &b, &c, &Vector3::new(0., 0., -1.)) > 0.);
+        assert!(orient3d(&a, &b, &c, &Vector3::new(0., 0., 1.)) < 0.);
+        assert_eq!(0., orient3d(&a, &b, &c, &Vector3::new(5., -3., 0.)));
+
+        // Coplanar points on the tilted plane z = x + y, the sums are exact.
+        let on_plane = |x: f32, y: f32| Vector3d::new(x as f64, y as f64, x as f64 + y as f64);
+        let (a, b, c) = (on_plane(0.1, 0.7), on_plane(1.3, 0.2), on_plane(0.6, 1.9));
+        assert_eq!(0., orient3d(&a, &b, &c, &on_plane(0.35, 0.45)));
+        let d = on_plane(0.35, 0.45);
+        assert!(orient3d(&a, &b, &c, &Vector3d::new(d.x, d.y, f64::from_bits(d.z.to_bits() + 1))) < 0.);
+    }
+
+    #[test]
+    fn test_orient3d_near_coplanar() {
+        // The same grid as in 2D, lifted so that the apex is above the plane.
+        let (q, r, s) = (Vector3d::new(12., 12., 0.), Vector3d::new(24., 24., 0.), Vector3d::new(0., 0., 1.));
+        let ulp = 0.5f64.powi(53);
+
+        for i in 0..32i32 {
+            for j in 0..32 {
+                let p = Vector3d::new(0.5 + i as f64 * ulp, 0.5 + j as f64 * ulp, 0.);
+                let expected = -(j - i).signum() as f64;
+                let det = orient3d(&p, &q, &r, &s);
+
+                assert_eq!(expected == 0., det == 0., "i:{i}, j:{j}");
+                if det != 0. {
+                    assert_eq!(expected, det.signum(), "i:{i}, j:{j}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_incircle() {
+        let (a, b, c) = (vec2(5., 0.), vec2(3., 4.), vec2(-4., 3.));
+
+        assert!(incircle(&a, &b, &c, &vec2(0., 0.)) > 0.);
+        assert!(incircle(&a, &b, &c, &vec2(6., 6.)) < 0.);
+        assert!(incircle(&c, &b, &a, &vec2(0., 0.)) < 0.);
+        assert_eq!(0., incircle(&a, &b, &c, &vec2(0., -5.)));
+        assert_eq!(0., incircle(&a, &b, &c, &vec2(-3., -4.)));
+
+        // Cocircular far from the origin, where the lifted terms are large.
+        let offset = Vector2d::new(1e7 + 0.25, -3e6 + 0.5);
+        let [a, b, c, d] = [(5., 0.), (3., 4.), (-4., 3.), (-5., 0.)].map(|(x, y)| &offset + &Vector2d::new(x, y));
+        assert_eq!(0., incircle(&a, &b, &c, &d));
+        assert!(incircle(&a, &b, &c, &Vector2d::new(f64::from_bits(d.x.to_bits() + 1), d.y)) > 0.);
+    }
+}
@@ -0,0 +1,388 @@
+use crate::math::{Mat3, Mat4, Scalar, Vec3};
+use bytemuck::{Pod, Zeroable};
+use std::ops::{Mul, Neg};
+
+/// Rotation quaternion `w + xi + yj + zk`, laid out as `(x, y, z, w)` like a
+/// WGSL `vec4f`.
+///
+/// `a * b` rotates by `b` first and then by `a`, the same order as matrices.
+#[repr(C)]
+#[derive(PartialEq, Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Quat<T> {
+    pub x: T,
+    pub y: T,
+    pub z: T,
+    pub w: T,
+}
+
+pub type Quaternion = Quat<f32>;
+pub type Quaterniond = Quat<f64>;
+
+// SAFETY: repr(C) with fields of a single Pod type has no padding.
+unsafe impl<T: Scalar> Zeroable for Quat<T> {}
+unsafe impl<T: Scalar> Pod for Quat<T> {}
+
+impl<T: Scalar> Quat<T> {
+    pub const IDENTITY: Self = Self {
+        x: T::ZERO,
+        y: T::ZERO,
+        z: T::ZERO,
+        w: T::ONE,
+    };
+
+    pub fn new(x: T, y: T, z: T, w: T) -> Self {
+        Self { x, y, z, w }
+    }
+
+    /// Rotation of `angle` radians counter-clockwise around `axis`.
+    pub fn from_axis_angle(axis: &Vec3<T>, angle: T) -> Self {
+        let (s, c) = (angle * T::HALF).sin_cos();
+        let a = &axis.normalize() * s;
+        Self::new(a.x, a.y, a.z, c)
+    }
+
+    /// Returns the unit axis and angle in radians, in `[0, 2π]`. The axis is
+    /// arbitrary (x) for the identity.
+    pub fn to_axis_angle(&self) -> (Vec3<T>, T) {
+        let q = self.normalize();
+        let s = (T::ONE - q.w * q.w).max(T::ZERO).sqrt();
+        let angle = T::TWO * q.w.clamp(-T::ONE, T::ONE).acos();
+        if s < T::from_f64(1e-6) {
+            (Vec3::new(T::ONE, T::ZERO, T::ZERO), angle)
+        } else {
+            (Vec3::new(q.x / s, q.y / s, q.z / s), angle)
+        }
+    }
+
+    /// Rotation by `roll` around x, then `pitch` around y, then `yaw` around
+    /// z, i.e. `Rz(yaw) * Ry(pitch) * Rx(roll)`. Angles in radians.
+    pub fn from_euler(roll: T, pitch: T, yaw: T) -> Self {
+        let (sr, cr) = (roll * T::HALF).sin_cos();
+        let (sp, cp) = (pitch * T::HALF).sin_cos();
+        let (sy, cy) = (yaw * T::HALF).sin_cos();
+
+        Self {
+            x: sr * cp * cy - cr * sp * sy,
+            y: cr * sp * cy + sr * cp * sy,
+            z: cr * cp * sy - sr * sp * cy,
+            w: cr * cp * cy + sr * sp * sy,
+        }
+    }
+
+    /// Inverse of `from_euler`, returns `(roll, pitch, yaw)` with pitch in
+    /// `[-π/2, π/2]`.
+    pub fn to_euler(&self) -> (T, T, T) {
+        let Self { x, y, z, w } = self.normalize();
+        let (one, two) = (T::ONE, T::TWO);
+
+        let roll = (two * (w * x + y * z)).atan2(one - two * (x * x + y * y));
+        let pitch = (two * (w * y - z * x)).clamp(-one, one).asin();
+        let yaw = (two * (w * z + x * y)).atan2(one - two * (y * y + z * z));
+
+        (roll, pitch, yaw)
+    }
+
+    /// Expects `m` to be a pure rotation.
+    pub fn from_matrix3(m: &Mat3<T>) -> Self {
+        let (one, quarter) = (T::ONE, T::from_f64(0.25));
+        let trace = m[(0, 0)] + m[(1, 1)] + m[(2, 2)];
+
+        // pick the largest component to divide by, for stability
+        let q = if trace > T::ZERO {
+            let s = (trace + one).sqrt() * T::TWO;
+            Self::new(
+                (m[(2, 1)] - m[(1, 2)]) / s,
+                (m[(0, 2)] - m[(2, 0)]) / s,
+                (m[(1, 0)] - m[(0, 1)]) / s,
+                s * quarter,
+            )
+        } else if m[(0, 0)] > m[(1, 1)] && m[(0, 0)] > m[(2, 2)] {
+            let s = (one + m[(0, 0)] - m[(1, 1)] - m[(2, 2)]).sqrt() * T::TWO;
+            Self::new(
+                s * quarter,
+                (m[(0, 1)] + m[(1, 0)]) / s,
+                (m[(0, 2)] + m[(2, 0)]) / s,
+                (m[(2, 1)] - m[(1, 2)]) / s,
+            )
+        } else if m[(1, 1)] > m[(2, 2)] {
+            let s = (one + m[(1, 1)] - m[(0, 0)] - m[(2, 2)]).sqrt() * T::TWO;
+            Self::new(
+                (m[(0, 1)] + m[(1, 0)]) / s,
+                s * quarter,
+                (m[(1, 2)] + m[(2, 1)]) / s,
+                (m[(0, 2)] - m[(2, 0)]) / s,
+            )
+        } else {
+            let s = (one + m[(2, 2)] - m[(0, 0)] - m[(1, 1)]).sqrt() * T::TWO;
+            Self::new(
+                (m[(0, 2)] + m[(2, 0)]) / s,
+                (m[(1, 2)] + m[(2, 1)]) / s,
+                s * quarter,
+                (m[(1, 0)] - m[(0, 1)]) / s,
+            )
+        };
+
+        q.normalize()
+    }
+
+    pub fn to_matrix3(&self) -> Mat3<T> {
+        let Self { x, y, z, w } = self.normalize();
+        let (one, two) = (T::ONE, T::TWO);
+
+        Mat3::from_rows([
+            [one - two * (y * y + z * z), two * (x * y - w * z), two * (x * z + w * y)],
+            [two * (x * y + w * z), one - two * (x * x + z * z), two * (y * z - w * x)],
+            [two * (x * z - w * y), two * (y * z + w * x), one - two * (x * x + y * y)],
+        ])
+    }
+
+    pub fn to_matrix4(&self) -> Mat4<T> {
+        Mat4::from(&self.to_matrix3())
+    }
+
+    pub fn dot(&self, rhs: &Self) -> T {
+        self.x * rhs.x + self.y * rhs.y + self.z * rhs.z + self.w * rhs.w
+    }
+
+    pub fn norm(&self) -> T {
+        self.dot(self).sqrt()
+    }
+
+    pub fn normalize(&self) -> Self {
+        let n = self.norm();
+        Self::new(self.x / n, self.y / n, self.z / n, self.w / n)
+    }
+
+    pub fn conjugate(&self) -> Self {
+        Self::new(-self.x, -self.y, -self.z, self.w)
+    }
+
+    pub fn inverse(&self) -> Self {
+        let n = self.dot(self);
+        let c = self.conjugate();
+        Self::new(c.x / n, c.y / n, c.z / n, c.w / n)
+    }
+
+    /// Rotates `v`, assuming `self` is a unit quaternion.
+    pub fn rotate(&self, v: &Vec3<T>) -> Vec3<T> {
+        // v + 2w(u × v) + 2u × (u × v)
+        let u = Vec3::new(self.x, self.y, self.z);
+        let t = &u.cross(v) * T::TWO;
+        &(v + &(&t * self.w)) + &u.cross(&t)
+    }
+
+    /// Normalized linear interpolation along the shorter arc. Cheaper than
+    /// `slerp` but does not have constant angular velocity.
+    pub fn nlerp(&self, rhs: &Self, t: T) -> Self {
+        let rhs = if self.dot(rhs) < T::ZERO { -rhs } else { *rhs };
+        Self::new(
+            self.x + (rhs.x - self.x) * t,
+            self.y + (rhs.y - self.y) * t,
+            self.z + (rhs.z - self.z) * t,
+            self.w + (rhs.w - self.w) * t,
+        )
+        .normalize()
+    }
+
+    /// Spherical linear interpolation along the shorter arc.
+    pub fn slerp(&self, rhs: &Self, t: T) -> Self {
+        let mut cos = self.dot(rhs);
+        let rhs = if cos < T::ZERO {
+            cos = -cos;
+            -rhs
+        } else {
+            *rhs
+        };
+
+        // nearly parallel, sin(θ) is too small to divide by
+        if cos > T::from_f64(0.9995) {
+            return self.nlerp(&rhs, t);
+        }
+
+        let theta = cos.acos();
+        let sin = theta.sin();
+        let a = ((T::ONE - t) * theta).sin() / sin;
+        let b = (t * theta).sin() / sin;
+
+        Self::new(
+            a * self.x + b * rhs.x,
+            a * self.y + b * rhs.y,
+            a * self.z + b * rhs.z,
+            a * self.w + b * rhs.w,
+        )
+    }
+
+    /// Converts the components to another precision, rounding when
+    /// narrowing.
+    pub fn cast<U: Scalar>(&self) -> Quat<U> {
+        Quat::new(
+            U::from_f64(self.x.to_f64()),
+            U::from_f64(self.y.to_f64()),
+            U::from_f64(self.z.to_f64()),
+            U::from_f64(self.w.to_f64()),
+        )
+    }
+}
+
+impl From<Quaternion> for Quaterniond {
+    fn from(q: Quaternion) -> Self {
+        q.cast()
+    }
+}
+
+impl<T: Scalar> From<&Mat3<T>> for Quat<T> {
+    fn from(m: &Mat3<T>) -> Self {
+        Self::from_matrix3(m)
+    }
+}
+
+impl<T: Scalar> From<&Mat4<T>> for Quat<T> {
+    fn from(m: &Mat4<T>) -> Self {
+        Self::from_matrix3(&m.to_matrix3())
+    }
+}
+
+impl<T: Scalar> From<&Quat<T>> for Mat3<T> {
+    fn from(q: &Quat<T>) -> Self {
+        q.to_matrix3()
+    }
+}
+
+impl<T: Scalar> From<&Quat<T>> for Mat4<T> {
+    fn from(q: &Quat<T>) -> Self {
+        q.to_matrix4()
+    }
+}
+
+impl<T: Scalar> Neg for &Quat<T> {
+    type Output = Quat<T>;
+
+    fn neg(self) -> Self::Output {
+        Quat::new(-self.x, -self.y, -self.z, -self.w)
+    }
+}
+
+impl<T: Scalar> Mul<&Quat<T>> for &Quat<T> {
+    type Output = Quat<T>;
+
+    fn mul(self, rhs: &Quat<T>) -> Self::Output {
+        Quat {
+            x: self.w * rhs.x + self.x * rhs.w + self.y * rhs.z - self.z * rhs.y,
+            y: self.w * rhs.y - self.x * rhs.z + self.y * rhs.w + self.z * rhs.x,
+            z: self.w * rhs.z + self.x * rhs.y - self.y * rhs.x + self.z * rhs.w,
+            w: self.w * rhs.w - self.x * rhs.x - self.y * rhs.y - self.z * rhs.z,
+        }
+    }
+}
+
+impl<T: Scalar> Mul<&Vec3<T>> for &Quat<T> {
+    type Output = Vec3<T>;
+
+    fn mul(self, rhs: &Vec3<T>) -> Self::Output {
+        self.rotate(rhs)
+    }
+}
+
+#[cfg(test)]
+mod test_quaternion {
+    use super::*;
+    use crate::math::{Matrix4, Vector3};
+    use std::f32::consts::{FRAC_PI_2, FRAC_PI_4, PI};
+
+    fn assert_vec_near(a: &Vector3, b: &Vector3) {
+        assert!((a - b).norm() < 1e-5, "{:?} != {:?}", a, b);
+    }
+
+    // q and -q are the same rotation
+    fn assert_rotation_near(a: &Quaternion, b: &Quaternion) {
+        assert!(a.dot(b).abs() > 1. - 1e-5, "{:?} != {:?}", a, b);
+    }
+
+    #[test]
+    fn test_rotate() {
+        let q = Quaternion::from_axis_angle(&Vector3::new(0., 0., 1.), FRAC_PI_2);
+
+        assert_vec_near(&Vector3::new(0., 1., 0.), &q.rotate(&Vector3::new(1., 0., 0.)));
+        assert_vec_near(&Vector3::new(-1., 0., 0.), &(&q * &Vector3::new(0., 1., 0.)));
+        assert_vec_near(&Vector3::new(0., 0., 1.), &q.rotate(&Vector3::new(0., 0., 1.)));
+        assert_eq!(Vector3::new(1., 2., 3.), Quaternion::IDENTITY.rotate(&Vector3::new(1., 2., 3.)));
+    }
+
+    #[test]
+    fn test_compose() {
+        let a = Quaternion::from_axis_angle(&Vector3::new(0., 0., 1.), FRAC_PI_2);
+        let b = Quaternion::from_axis_angle(&Vector3::new(1., 0., 0.), FRAC_PI_2);
+        let v = Vector3::new(0., 1., 0.);
+
+        // b first, then a
+        assert_vec_near(&a.rotate(&b.rotate(&v)), &(&a * &b).rotate(&v));
+        assert_rotation_near(&Quaternion::from_axis_angle(&Vector3::new(0., 0., 1.), PI), &(&a * &a));
+        assert_rotation_near(&Quaternion::IDENTITY, &(&a * &a.inverse()));
+        assert_rotation_near(&a.conjugate(), &a.inverse());
+    }
+
+    #[test]
+    fn test_axis_angle() {
+        let axis = Vector3::new(1., 2., 3.).normalize();
+        let (a, angle) = Quaternion::from_axis_angle(&axis, 1.2).to_axis_angle();
+
+        assert_vec_near(&axis, &a);
+        assert!((angle - 1.2).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_matrix() {
+        let q = Quaternion::from_axis_angle(&Vector3::new(1., 2., 3.), 2.);
+        let m = q.to_matrix3();
+        let v = Vector3::new(-1., 0.5, 2.);
+
+        assert_vec_near(&q.rotate(&v), &(&m * &v));
+        assert!((m.determinant() - 1.).abs() < 1e-5);
+        assert_vec_near(&q.rotate(&v), &Matrix4::from(&q).transform_vector(&v));
+
+        assert_rotation_near(&q, &Quaternion::from(&m));
+        assert_rotation_near(&q, &Quaternion::from(&q.to_matrix4()));
+
+        // exercise every branch of from_matrix3
+        for axis in [Vector3::new(1., 0., 0.), Vector3::new(0., 1., 0.), Vector3::new(0., 0., 1.)] {
+            let q = Quaternion::from_axis_angle(&axis, 3.);
+            assert_rotation_near(&q, &Quaternion::from_matrix3(&q.to_matrix3()));
+        }
+    }
+
+    #[test]
+    fn test_euler() {
+        let (roll, pitch, yaw) = (0.3, -0.7, 1.1);
+        let q = Quaternion::from_euler(roll, pitch, yaw);
+
+        let rx = Quaternion::from_axis_angle(&Vector3::new(1., 0., 0.), roll);
+        let ry = Quaternion::from_axis_angle(&Vector3::new(0., 1., 0.), pitch);
+        let rz = Quaternion::from_axis_angle(&Vector3::new(0., 0., 1.), yaw);
+        assert_rotation_near(&(&rz * &(&ry * &rx)), &q);
+
+        let (r, p, y) = q.to_euler();
+        assert!((r - roll).abs() < 1e-5);
+        assert!((p - pitch).abs() < 1e-5);
+        assert!((y - yaw).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_slerp() {
+        let axis = Vector3::new(0., 1., 0.);
+        let a = Quaternion::IDENTITY;
+        let b = Quaternion::from_axis_angle(&axis, FRAC_PI_2);
+
+        assert_rotation_near(&a, &a.slerp(&b, 0.));
+        assert_rotation_near(&b, &a.slerp(&b, 1.));
+        assert_rotation_near(&Quaternion::from_axis_angle(&axis, FRAC_PI_4), &a.slerp(&b, 0.5));
+        assert_rotation_near(&Quaternion::from_axis_angle(&axis, FRAC_PI_4), &a.nlerp(&b, 0.5));
+
+        // constant angular velocity
+        let (_, angle) = a.slerp(&b, 0.25).to_axis_angle();
+        assert!((angle - FRAC_PI_2 / 4.).abs() < 1e-5);
+
+        // takes the shorter arc when the signs disagree
+        assert_rotation_near(&Quaternion::from_axis_angle(&axis, FRAC_PI_4), &a.slerp(&-&b, 0.5));
+    }
+}
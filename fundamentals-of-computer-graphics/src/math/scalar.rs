@@ -0,0 +1,128 @@
+use bytemuck::Pod;
+use std::fmt::Debug;
+use std::iter::Sum;
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+
+/// Floating-point scalar the math types are generic over, implemented for
+/// `f32` and `f64`.
+pub trait Scalar:
+    Pod
+    + Debug
+    + PartialOrd
+    + Default
+    + Sum
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+    + Neg<Output = Self>
+    + AddAssign
+    + SubAssign
+    + MulAssign
+    + DivAssign
+{
+    const ZERO: Self;
+    const ONE: Self;
+    const TWO: Self;
+    const HALF: Self;
+    const PI: Self;
+    const EPSILON: Self;
+    const INFINITY: Self;
+    const NEG_INFINITY: Self;
+
+    /// Rounds to the nearest representable value for `f32`.
+    fn from_f64(v: f64) -> Self;
+    fn to_f64(self) -> f64;
+
+    fn sqrt(self) -> Self;
+    fn abs(self) -> Self;
+    fn min(self, rhs: Self) -> Self;
+    fn max(self, rhs: Self) -> Self;
+    fn clamp(self, min: Self, max: Self) -> Self;
+    fn copysign(self, sign: Self) -> Self;
+    fn sin(self) -> Self;
+    fn cos(self) -> Self;
+    fn sin_cos(self) -> (Self, Self);
+    fn tan(self) -> Self;
+    fn asin(self) -> Self;
+    fn acos(self) -> Self;
+    fn atan2(self, rhs: Self) -> Self;
+}
+
+macro_rules! impl_scalar {
+    ($t:ident) => {
+        impl Scalar for $t {
+            const ZERO: Self = 0.;
+            const ONE: Self = 1.;
+            const TWO: Self = 2.;
+            const HALF: Self = 0.5;
+            const PI: Self = std::$t::consts::PI;
+            const EPSILON: Self = $t::EPSILON;
+            const INFINITY: Self = $t::INFINITY;
+            const NEG_INFINITY: Self = $t::NEG_INFINITY;
+
+            fn from_f64(v: f64) -> Self {
+                v as $t
+            }
+
+            fn to_f64(self) -> f64 {
+                self as f64
+            }
+
+            fn sqrt(self) -> Self {
+                $t::sqrt(self)
+            }
+
+            fn abs(self) -> Self {
+                $t::abs(self)
+            }
+
+            fn min(self, rhs: Self) -> Self {
+                $t::min(self, rhs)
+            }
+
+            fn max(self, rhs: Self) -> Self {
+                $t::max(self, rhs)
+            }
+
+            fn clamp(self, min: Self, max: Self) -> Self {
+                $t::clamp(self, min, max)
+            }
+
+            fn copysign(self, sign: Self) -> Self {
+                $t::copysign(self, sign)
+            }
+
+            fn sin(self) -> Self {
+                $t::sin(self)
+            }
+
+            fn cos(self) -> Self {
+                $t::cos(self)
+            }
+
+            fn sin_cos(self) -> (Self, Self) {
+                $t::sin_cos(self)
+            }
+
+            fn tan(self) -> Self {
+                $t::tan(self)
+            }
+
+            fn asin(self) -> Self {
+                $t::asin(self)
+            }
+
+            fn acos(self) -> Self {
+                $t::acos(self)
+            }
+
+            fn atan2(self, rhs: Self) -> Self {
+                $t::atan2(self, rhs)
+            }
+        }
+    };
+}
+
+impl_scalar!(f32);
+impl_scalar!(f64);
@@ -0,0 +1,309 @@
+use crate::math::Vector3;
+use std::array;
+use std::ops::{Add, BitAnd, BitOr, Div, Mul, Neg, Not, Sub};
+
+/// `N` f32 lanes. The lane loops are written so the compiler can vectorize
+/// them, and do exactly the scalar operations per lane so that packet
+/// results are bit-identical to the scalar path.
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub struct F32xN<const N: usize>(pub [f32; N]);
+
+/// `N` boolean lanes, e.g. which rays of a packet hit.
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub struct MaskN<const N: usize>(pub [bool; N]);
+
+/// Structure-of-arrays packet of `N` vectors.
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub struct Vec3xN<const N: usize> {
+    pub x: F32xN<N>,
+    pub y: F32xN<N>,
+    pub z: F32xN<N>,
+}
+
+pub type F32x4 = F32xN<4>;
+pub type F32x8 = F32xN<8>;
+pub type Mask4 = MaskN<4>;
+pub type Mask8 = MaskN<8>;
+pub type Vector3x4 = Vec3xN<4>;
+pub type Vector3x8 = Vec3xN<8>;
+
+impl<const N: usize> F32xN<N> {
+    pub fn splat(v: f32) -> Self {
+        Self([v; N])
+    }
+
+    pub fn map(&self, f: impl Fn(f32) -> f32) -> Self {
+        Self(self.0.map(f))
+    }
+
+    pub fn zip(&self, rhs: &Self, f: impl Fn(f32, f32) -> f32) -> Self {
+        Self(array::from_fn(|i| f(self.0[i], rhs.0[i])))
+    }
+
+    pub fn sqrt(&self) -> Self {
+        self.map(f32::sqrt)
+    }
+
+    pub fn min(&self, rhs: &Self) -> Self {
+        self.zip(rhs, f32::min)
+    }
+
+    pub fn max(&self, rhs: &Self) -> Self {
+        self.zip(rhs, f32::max)
+    }
+
+    pub fn lt(&self, rhs: &Self) -> MaskN<N> {
+        MaskN(array::from_fn(|i| self.0[i] < rhs.0[i]))
+    }
+
+    pub fn le(&self, rhs: &Self) -> MaskN<N> {
+        MaskN(array::from_fn(|i| self.0[i] <= rhs.0[i]))
+    }
+
+    pub fn gt(&self, rhs: &Self) -> MaskN<N> {
+        MaskN(array::from_fn(|i| self.0[i] > rhs.0[i]))
+    }
+
+    pub fn ge(&self, rhs: &Self) -> MaskN<N> {
+        MaskN(array::from_fn(|i| self.0[i] >= rhs.0[i]))
+    }
+
+    /// `a` where `mask` is set, `b` elsewhere.
+    pub fn select(mask: &MaskN<N>, a: &Self, b: &Self) -> Self {
+        Self(array::from_fn(|i| if mask.0[i] { a.0[i] } else { b.0[i] }))
+    }
+}
+
+impl<const N: usize> MaskN<N> {
+    pub fn splat(v: bool) -> Self {
+        Self([v; N])
+    }
+
+    pub fn any(&self) -> bool {
+        self.0.iter().any(|&m| m)
+    }
+
+    pub fn all(&self) -> bool {
+        self.0.iter().all(|&m| m)
+    }
+}
+
+impl<const N: usize> Vec3xN<N> {
+    pub fn new(x: F32xN<N>, y: F32xN<N>, z: F32xN<N>) -> Self {
+        Self { x, y, z }
+    }
+
+    /// The same vector in every lane.
+    pub fn splat(v: &Vector3) -> Self {
+        Self::new(F32xN::splat(v.x), F32xN::splat(v.y), F32xN::splat(v.z))
+    }
+
+    pub fn from_lanes(v: &[Vector3; N]) -> Self {
+        Self::new(
+            F32xN(array::from_fn(|i| v[i].x)),
+            F32xN(array::from_fn(|i| v[i].y)),
+            F32xN(array::from_fn(|i| v[i].z)),
+        )
+    }
+
+    pub fn lane(&self, i: usize) -> Vector3 {
+        Vector3::new(self.x.0[i], self.y.0[i], self.z.0[i])
+    }
+
+    pub fn dot(&self, rhs: &Self) -> F32xN<N> {
+        &(&(&self.x * &rhs.x) + &(&self.y * &rhs.y)) + &(&self.z * &rhs.z)
+    }
+
+    pub fn cross(&self, rhs: &Self) -> Self {
+        Self {
+            x: &(&self.y * &rhs.z) - &(&self.z * &rhs.y),
+            y: &(&self.z * &rhs.x) - &(&self.x * &rhs.z),
+            z: &(&self.x * &rhs.y) - &(&self.y * &rhs.x),
+        }
+    }
+
+    pub fn norm(&self) -> F32xN<N> {
+        self.dot(self).sqrt()
+    }
+
+    pub fn normalize(&self) -> Self {
+        self / &self.norm()
+    }
+
+    /// `a` in the lanes where `mask` is set, `b` elsewhere.
+    pub fn select(mask: &MaskN<N>, a: &Self, b: &Self) -> Self {
+        Self {
+            x: F32xN::select(mask, &a.x, &b.x),
+            y: F32xN::select(mask, &a.y, &b.y),
+            z: F32xN::select(mask, &a.z, &b.z),
+        }
+    }
+}
+
+macro_rules! impl_lane_op {
+    ($op:ident, $f:ident) => {
+        impl<const N: usize> $op<&F32xN<N>> for &F32xN<N> {
+            type Output = F32xN<N>;
+
+            fn $f(self, rhs: &F32xN<N>) -> Self::Output {
+                F32xN(array::from_fn(|i| self.0[i].$f(rhs.0[i])))
+            }
+        }
+
+        impl<const N: usize> $op<f32> for &F32xN<N> {
+            type Output = F32xN<N>;
+
+            fn $f(self, rhs: f32) -> Self::Output {
+                F32xN(array::from_fn(|i| self.0[i].$f(rhs)))
+            }
+        }
+
+        impl<const N: usize> $op<&F32xN<N>> for f32 {
+            type Output = F32xN<N>;
+
+            fn $f(self, rhs: &F32xN<N>) -> Self::Output {
+                F32xN(array::from_fn(|i| self.$f(rhs.0[i])))
+            }
+        }
+    };
+}
+
+impl_lane_op!(Add, add);
+impl_lane_op!(Sub, sub);
+impl_lane_op!(Mul, mul);
+impl_lane_op!(Div, div);
+
+impl<const N: usize> Neg for &F32xN<N> {
+    type Output = F32xN<N>;
+
+    fn neg(self) -> Self::Output {
+        self.map(|v| -v)
+    }
+}
+
+impl<const N: usize> BitAnd<&MaskN<N>> for &MaskN<N> {
+    type Output = MaskN<N>;
+
+    fn bitand(self, rhs: &MaskN<N>) -> Self::Output {
+        MaskN(array::from_fn(|i| self.0[i] && rhs.0[i]))
+    }
+}
+
+impl<const N: usize> BitOr<&MaskN<N>> for &MaskN<N> {
+    type Output = MaskN<N>;
+
+    fn bitor(self, rhs: &MaskN<N>) -> Self::Output {
+        MaskN(array::from_fn(|i| self.0[i] || rhs.0[i]))
+    }
+}
+
+impl<const N: usize> Not for &MaskN<N> {
+    type Output = MaskN<N>;
+
+    fn not(self) -> Self::Output {
+        MaskN(self.0.map(|m| !m))
+    }
+}
+
+impl<const N: usize> Add<&Vec3xN<N>> for &Vec3xN<N> {
+    type Output = Vec3xN<N>;
+
+    fn add(self, rhs: &Vec3xN<N>) -> Self::Output {
+        Vec3xN::new(&self.x + &rhs.x, &self.y + &rhs.y, &self.z + &rhs.z)
+    }
+}
+
+impl<const N: usize> Sub<&Vec3xN<N>> for &Vec3xN<N> {
+    type Output = Vec3xN<N>;
+
+    fn sub(self, rhs: &Vec3xN<N>) -> Self::Output {
+        Vec3xN::new(&self.x - &rhs.x, &self.y - &rhs.y, &self.z - &rhs.z)
+    }
+}
+
+impl<const N: usize> Neg for &Vec3xN<N> {
+    type Output = Vec3xN<N>;
+
+    fn neg(self) -> Self::Output {
+        Vec3xN::new(-&self.x, -&self.y, -&self.z)
+    }
+}
+
+/// Scales each lane's vector by that lane's scalar.
+impl<const N: usize> Mul<&F32xN<N>> for &Vec3xN<N> {
+    type Output = Vec3xN<N>;
+
+    fn mul(self, rhs: &F32xN<N>) -> Self::Output {
+        Vec3xN::new(&self.x * rhs, &self.y * rhs, &self.z * rhs)
+    }
+}
+
+impl<const N: usize> Div<&F32xN<N>> for &Vec3xN<N> {
+    type Output = Vec3xN<N>;
+
+    fn div(self, rhs: &F32xN<N>) -> Self::Output {
+        Vec3xN::new(&self.x / rhs, &self.y / rhs, &self.z / rhs)
+    }
+}
+
+#[cfg(test)]
+mod test_simd {
+    use super::*;
+
+    fn lanes() -> [Vector3; 4] {
+        [
+            Vector3::new(1., 2., 3.),
+            Vector3::new(-1., 0.5, 2.),
+            Vector3::new(0., 0., 1.),
+            Vector3::new(3., -4., 0.),
+        ]
+    }
+
+    #[test]
+    fn test_lanes() {
+        let v = Vector3x4::from_lanes(&lanes());
+
+        for (i, l) in lanes().iter().enumerate() {
+            assert_eq!(*l, v.lane(i));
+        }
+        assert_eq!(Vector3::new(1., 2., 3.), Vector3x8::splat(&Vector3::new(1., 2., 3.)).lane(7));
+    }
+
+    #[test]
+    fn test_matches_scalar() {
+        let a = Vector3x4::from_lanes(&lanes());
+        let b = Vector3x4::splat(&Vector3::new(0.3, -0.7, 1.1));
+
+        let (dot, cross, norm, normalized, sum) = (a.dot(&b), a.cross(&b), a.norm(), a.normalize(), &a + &b);
+        for (i, l) in lanes().iter().enumerate() {
+            let s = b.lane(i);
+            assert_eq!(l.dot(&s), dot.0[i]);
+            assert_eq!(l.cross(&s), cross.lane(i));
+            assert_eq!(l.norm(), norm.0[i]);
+            assert_eq!(l.normalize(), normalized.lane(i));
+            assert_eq!(l + &s, sum.lane(i));
+            assert_eq!(l - &s, (&a - &b).lane(i));
+            assert_eq!(-l, (-&a).lane(i));
+        }
+    }
+
+    #[test]
+    fn test_select() {
+        let a = F32xN([1., 2., 3., 4.]);
+        let b = F32x4::splat(2.5);
+        let mask = a.lt(&b);
+
+        assert_eq!(MaskN([true, true, false, false]), mask);
+        assert_eq!(MaskN([false, true, false, false]), &mask & &a.ge(&F32x4::splat(2.)));
+        assert_eq!(MaskN([true, true, true, false]), &mask | &a.le(&F32x4::splat(3.)));
+        assert_eq!(MaskN([false, false, true, true]), !&mask);
+        assert!(mask.any() && !mask.all());
+        assert_eq!(F32xN([1., 2., 2.5, 2.5]), F32x4::select(&mask, &a, &b));
+
+        let u = Vector3x4::from_lanes(&lanes());
+        let v = Vector3x4::splat(&Vector3::new(0., 0., 0.));
+        let s = Vector3x4::select(&mask, &u, &v);
+        assert_eq!(lanes()[1], s.lane(1));
+        assert_eq!(Vector3::new(0., 0., 0.), s.lane(2));
+    }
+}
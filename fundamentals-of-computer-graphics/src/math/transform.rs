@@ -0,0 +1,317 @@
+use crate::math::{Mat4, Onb, Quat, Scalar, Vec3};
+use std::ops::Mul;
+
+/// Affine transform that carries its inverse along, so inverting and
+/// transforming normals never needs a 4x4 inversion.
+///
+/// `a * b` applies `b` first and then `a`.
+///
+/// Serialized as its matrix, the inverse is recomputed when deserializing.
+#[derive(PartialEq, Debug, Copy, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(
+        try_from = "Mat4<T>",
+        into = "Mat4<T>",
+        bound(serialize = "T: Scalar + serde::Serialize", deserialize = "T: Scalar + serde::Deserialize<'de>")
+    )
+)]
+pub struct Xform<T> {
+    m: Mat4<T>,
+    inv: Mat4<T>,
+}
+
+pub type Transform = Xform<f32>;
+pub type Transformd = Xform<f64>;
+
+impl<T: Scalar> Xform<T> {
+    pub const IDENTITY: Self = Self {
+        m: Mat4::IDENTITY,
+        inv: Mat4::IDENTITY,
+    };
+
+    /// Returns `None` when `m` is singular.
+    pub fn new(m: Mat4<T>) -> Option<Self> {
+        m.inverse().map(|inv| Self { m, inv })
+    }
+
+    pub fn translate(t: &Vec3<T>) -> Self {
+        let (o, l) = (T::ZERO, T::ONE);
+        let matrix = |t: &Vec3<T>| {
+            Mat4::from_rows([
+                [l, o, o, t.x],
+                [o, l, o, t.y],
+                [o, o, l, t.z],
+                [o, o, o, l],
+            ])
+        };
+
+        Self {
+            m: matrix(t),
+            inv: matrix(&-t),
+        }
+    }
+
+    /// Scales by `s.x`, `s.y`, `s.z` along the axes; none may be zero.
+    pub fn scale(s: &Vec3<T>) -> Self {
+        let (o, l) = (T::ZERO, T::ONE);
+        let matrix = |x: T, y: T, z: T| {
+            Mat4::from_rows([
+                [x, o, o, o],
+                [o, y, o, o],
+                [o, o, z, o],
+                [o, o, o, l],
+            ])
+        };
+
+        Self {
+            m: matrix(s.x, s.y, s.z),
+            inv: matrix(l / s.x, l / s.y, l / s.z),
+        }
+    }
+
+    /// Rotation of `angle` radians counter-clockwise around `axis`.
+    pub fn rotate(axis: &Vec3<T>, angle: T) -> Self {
+        Self::from_quaternion(&Quat::from_axis_angle(axis, angle))
+    }
+
+    pub fn rotate_x(angle: T) -> Self {
+        Self::rotate(&Onb::CANONICAL.u, angle)
+    }
+
+    pub fn rotate_y(angle: T) -> Self {
+        Self::rotate(&Onb::CANONICAL.v, angle)
+    }
+
+    pub fn rotate_z(angle: T) -> Self {
+        Self::rotate(&Onb::CANONICAL.w, angle)
+    }
+
+    pub fn from_quaternion(q: &Quat<T>) -> Self {
+        let m = q.to_matrix4();
+
+        // rotations are orthogonal
+        Self { m, inv: m.transpose() }
+    }
+
+    /// World-to-camera transform for a camera at `eye` looking at `target`.
+    /// The camera looks down its -w axis with v up, as in FCG 7.1.3.
+    pub fn look_at(eye: &Vec3<T>, target: &Vec3<T>, up: &Vec3<T>) -> Self {
+        let Onb { u, v, w } = Onb::from_two_vectors(&(eye - target), up);
+        let (o, l) = (T::ZERO, T::ONE);
+
+        // camera-to-world is [u v w e]; the rotation part inverts by transpose
+        let to_world = Mat4::from_rows([
+            [u.x, v.x, w.x, eye.x],
+            [u.y, v.y, w.y, eye.y],
+            [u.z, v.z, w.z, eye.z],
+            [o, o, o, l],
+        ]);
+        let to_camera = Mat4::from_rows([
+            [u.x, u.y, u.z, -u.dot(eye)],
+            [v.x, v.y, v.z, -v.dot(eye)],
+            [w.x, w.y, w.z, -w.dot(eye)],
+            [o, o, o, l],
+        ]);
+
+        Self {
+            m: to_camera,
+            inv: to_world,
+        }
+    }
+
+    pub fn matrix(&self) -> &Mat4<T> {
+        &self.m
+    }
+
+    pub fn inverse_matrix(&self) -> &Mat4<T> {
+        &self.inv
+    }
+
+    pub fn inverse(&self) -> Self {
+        Self {
+            m: self.inv,
+            inv: self.m,
+        }
+    }
+
+    /// `next` applied after `self`, i.e. `next * self`.
+    pub fn then(&self, next: &Self) -> Self {
+        next * self
+    }
+
+    pub fn transform_point(&self, p: &Vec3<T>) -> Vec3<T> {
+        self.m.transform_point(p)
+    }
+
+    pub fn transform_vector(&self, v: &Vec3<T>) -> Vec3<T> {
+        self.m.transform_vector(v)
+    }
+
+    /// Transforms a surface normal by the inverse transpose (FCG 6.2.2), so
+    /// it stays perpendicular to transformed tangents. The result is not
+    /// normalized.
+    pub fn transform_normal(&self, n: &Vec3<T>) -> Vec3<T> {
+        let m = &self.inv;
+        Vec3::new(
+            m[(0, 0)] * n.x + m[(1, 0)] * n.y + m[(2, 0)] * n.z,
+            m[(0, 1)] * n.x + m[(1, 1)] * n.y + m[(2, 1)] * n.z,
+            m[(0, 2)] * n.x + m[(1, 2)] * n.y + m[(2, 2)] * n.z,
+        )
+    }
+
+    /// Converts both matrices to another precision, rounding when narrowing.
+    pub fn cast<U: Scalar>(&self) -> Xform<U> {
+        Xform {
+            m: self.m.cast(),
+            inv: self.inv.cast(),
+        }
+    }
+}
+
+impl From<Transform> for Transformd {
+    fn from(t: Transform) -> Self {
+        t.cast()
+    }
+}
+
+impl<T: Scalar> Mul<&Xform<T>> for &Xform<T> {
+    type Output = Xform<T>;
+
+    fn mul(self, rhs: &Xform<T>) -> Self::Output {
+        Xform {
+            m: &self.m * &rhs.m,
+            inv: &rhs.inv * &self.inv,
+        }
+    }
+}
+
+impl<T: Scalar> From<Xform<T>> for Mat4<T> {
+    fn from(t: Xform<T>) -> Self {
+        t.m
+    }
+}
+
+impl<T: Scalar> TryFrom<Mat4<T>> for Xform<T> {
+    type Error = &'static str;
+
+    fn try_from(m: Mat4<T>) -> Result<Self, Self::Error> {
+        Self::new(m).ok_or("singular transform matrix")
+    }
+}
+
+#[cfg(test)]
+mod test_transform {
+    use super::*;
+    use crate::math::{Matrix4, Vector3, Vector3d};
+    use std::f32::consts::FRAC_PI_2;
+
+    fn assert_near(a: &Vector3, b: &Vector3) {
+        assert!((a - b).norm() < 1e-5, "{:?} != {:?}", a, b);
+    }
+
+    fn assert_inverse(t: &Transform) {
+        let m = t.matrix() * t.inverse_matrix();
+        for c in 0..4 {
+            for r in 0..4 {
+                let expected = if r == c { 1. } else { 0. };
+                assert!((m[(r, c)] - expected).abs() < 1e-5, "{:?}", t);
+            }
+        }
+    }
+
+    #[test]
+    fn test_translate() {
+        let t = Transform::translate(&Vector3::new(1., 2., 3.));
+        let p = Vector3::new(1., 1., 1.);
+
+        assert_eq!(Vector3::new(2., 3., 4.), t.transform_point(&p));
+        assert_eq!(p, t.transform_vector(&p));
+        assert_eq!(p, t.transform_normal(&p));
+        assert_eq!(p, t.inverse().transform_point(&Vector3::new(2., 3., 4.)));
+        assert_inverse(&t);
+    }
+
+    #[test]
+    fn test_scale() {
+        let s = Transform::scale(&Vector3::new(2., 4., 1.));
+
+        assert_eq!(Vector3::new(2., 4., 1.), s.transform_point(&Vector3::new(1., 1., 1.)));
+        assert_inverse(&s);
+    }
+
+    #[test]
+    fn test_rotate() {
+        let r = Transform::rotate_z(FRAC_PI_2);
+
+        assert_near(&Vector3::new(0., 1., 0.), &r.transform_point(&Vector3::new(1., 0., 0.)));
+        assert_near(&Vector3::new(0., 0., 1.), &Transform::rotate_x(FRAC_PI_2).transform_vector(&Vector3::new(0., 1., 0.)));
+        assert_near(&Vector3::new(1., 0., 0.), &Transform::rotate_y(FRAC_PI_2).transform_vector(&Vector3::new(0., 0., 1.)));
+        assert_inverse(&r);
+        assert_inverse(&Transform::rotate(&Vector3::new(1., 2., 3.), 0.7));
+    }
+
+    #[test]
+    fn test_compose() {
+        let t = Transform::translate(&Vector3::new(1., 0., 0.));
+        let r = Transform::rotate_z(FRAC_PI_2);
+        let p = Vector3::new(1., 0., 0.);
+
+        // rotate first, then translate
+        assert_near(&Vector3::new(1., 1., 0.), &(&t * &r).transform_point(&p));
+        assert_near(&Vector3::new(1., 1., 0.), &r.then(&t).transform_point(&p));
+        assert_near(&Vector3::new(0., 2., 0.), &(&r * &t).transform_point(&p));
+        assert_inverse(&(&t * &r));
+        assert_eq!(Transform::IDENTITY, Transform::new(Matrix4::IDENTITY).unwrap());
+        assert_eq!(None, Transform::new(Matrix4::ZERO));
+    }
+
+    #[test]
+    fn test_transform_normal() {
+        // a plane with normal (1, 1, 0) squashed along x keeps its normal
+        // perpendicular only under the inverse transpose
+        let s = Transform::scale(&Vector3::new(0.5, 1., 1.));
+        let n = Vector3::new(1., 1., 0.);
+        let tangent = Vector3::new(1., -1., 0.);
+
+        let n2 = s.transform_normal(&n);
+        let t2 = s.transform_vector(&tangent);
+        assert!(n2.dot(&t2).abs() < 1e-6);
+        assert!(s.transform_vector(&n).dot(&t2).abs() > 0.1);
+
+        // translation never affects normals
+        let t = &Transform::translate(&Vector3::new(5., 6., 7.)) * &s;
+        assert_near(&n2, &t.transform_normal(&n));
+    }
+
+    #[test]
+    fn test_look_at() {
+        let eye = Vector3::new(0., 0., 5.);
+        let view = Transform::look_at(&eye, &Vector3::new(0., 0., 0.), &Vector3::new(0., 1., 0.));
+
+        assert_near(&Vector3::new(0., 0., 0.), &view.transform_point(&eye));
+        assert_near(&Vector3::new(0., 0., -5.), &view.transform_point(&Vector3::new(0., 0., 0.)));
+        assert_near(&Vector3::new(1., 1., -5.), &view.transform_point(&Vector3::new(1., 1., 0.)));
+        assert_inverse(&view);
+
+        let view = Transform::look_at(&Vector3::new(3., 2., 1.), &Vector3::new(-1., 0., 2.), &Vector3::new(0., 1., 0.));
+        let target = view.transform_point(&Vector3::new(-1., 0., 2.));
+        assert!(target.x.abs() < 1e-5 && target.y.abs() < 1e-5 && target.z < 0.);
+        assert_inverse(&view);
+    }
+
+    #[test]
+    fn test_precision() {
+        let t = &Transform::translate(&Vector3::new(1., 2., 3.)) * &Transform::rotate_y(0.3);
+        assert_eq!(t, Transformd::from(t).cast());
+
+        // a round trip far from the origin, where f32 steps are whole units
+        let p = Vector3d::new(0.1, 0.2, 0.3);
+        let far = Transformd::translate(&Vector3d::new(1e7, -1e7, 1e7));
+        assert!((&p - &far.inverse().transform_point(&far.transform_point(&p))).norm() < 1e-8);
+
+        let (far, p) = (far.cast::<f32>(), p.cast::<f32>());
+        assert!((&p - &far.inverse().transform_point(&far.transform_point(&p))).norm() > 0.1);
+    }
+}
@@ -0,0 +1,362 @@
+use crate::math::{Aabb, Scalar, Vec3};
+
+/// Triangle with corners `a`, `b`, `c`, counter-clockwise around its normal.
+///
+/// Barycentric coordinates `(α, β, γ)` follow FCG 2.7: a point is
+/// `α a + β b + γ c`, or `a + β (b - a) + γ (c - a)`.
+#[derive(PartialEq, Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Tri<T> {
+    pub a: Vec3<T>,
+    pub b: Vec3<T>,
+    pub c: Vec3<T>,
+}
+
+pub type Triangle = Tri<f32>;
+pub type Triangled = Tri<f64>;
+
+impl<T: Scalar> Tri<T> {
+    pub fn new(a: Vec3<T>, b: Vec3<T>, c: Vec3<T>) -> Self {
+        Self { a, b, c }
+    }
+
+    /// `(b - a) × (c - a)`, twice the area long.
+    fn scaled_normal(&self) -> Vec3<T> {
+        (&self.b - &self.a).cross(&(&self.c - &self.a))
+    }
+
+    pub fn normal(&self) -> Vec3<T> {
+        self.scaled_normal().normalize()
+    }
+
+    pub fn area(&self) -> T {
+        T::HALF * self.scaled_normal().norm()
+    }
+
+    pub fn centroid(&self) -> Vec3<T> {
+        &(&(&self.a + &self.b) + &self.c) / T::from_f64(3.)
+    }
+
+    pub fn bounding_box(&self) -> Aabb<T> {
+        Aabb::from_points([&self.a, &self.b, &self.c])
+    }
+
+    /// Point with barycentric coordinates `(1 - β - γ, β, γ)`.
+    pub fn point(&self, beta: T, gamma: T) -> Vec3<T> {
+        &(&self.a + &(&(&self.b - &self.a) * beta)) + &(&(&self.c - &self.a) * gamma)
+    }
+
+    /// Barycentric coordinates `(α, β, γ)` of `p` projected onto the plane of
+    /// the triangle, from signed sub-triangle areas (FCG 2.7.2). `p` is
+    /// inside when all three are non-negative.
+    pub fn barycentric(&self, p: &Vec3<T>) -> (T, T, T) {
+        let n = self.scaled_normal();
+        let na = (&self.c - &self.b).cross(&(p - &self.b));
+        let nb = (&self.a - &self.c).cross(&(p - &self.c));
+        let nc = (&self.b - &self.a).cross(&(p - &self.a));
+
+        let nn = n.dot(&n);
+        (n.dot(&na) / nn, n.dot(&nb) / nn, n.dot(&nc) / nn)
+    }
+
+    /// Point on the triangle nearest to `p`, by Voronoi region of the corners
+    /// and edges (Ericson, Real-Time Collision Detection 5.1.5).
+    pub fn closest_point(&self, p: &Vec3<T>) -> Vec3<T> {
+        let (a, b, c) = (&self.a, &self.b, &self.c);
+        let ab = b - a;
+        let ac = c - a;
+        let zero = T::ZERO;
+
+        let ap = p - a;
+        let d1 = ab.dot(&ap);
+        let d2 = ac.dot(&ap);
+        if d1 <= zero && d2 <= zero {
+            return *a;
+        }
+
+        let bp = p - b;
+        let d3 = ab.dot(&bp);
+        let d4 = ac.dot(&bp);
+        if d3 >= zero && d4 <= d3 {
+            return *b;
+        }
+
+        let vc = d1 * d4 - d3 * d2;
+        if vc <= zero && d1 >= zero && d3 <= zero {
+            return a + &(&ab * (d1 / (d1 - d3)));
+        }
+
+        let cp = p - c;
+        let d5 = ab.dot(&cp);
+        let d6 = ac.dot(&cp);
+        if d6 >= zero && d5 <= d6 {
+            return *c;
+        }
+
+        let vb = d5 * d2 - d1 * d6;
+        if vb <= zero && d2 >= zero && d6 <= zero {
+            return a + &(&ac * (d2 / (d2 - d6)));
+        }
+
+        let va = d3 * d6 - d5 * d4;
+        if va <= zero && d4 - d3 >= zero && d5 - d6 >= zero {
+            let w = (d4 - d3) / ((d4 - d3) + (d5 - d6));
+            return b + &(&(c - b) * w);
+        }
+
+        let denom = T::ONE / (va + vb + vc);
+        self.point(vb * denom, vc * denom)
+    }
+
+    /// `(t, β, γ)` where the ray `e + t d` hits the triangle with `t` in
+    /// `[t0, t1]`, solving the 3x3 system by Cramer's rule (FCG 4.4.2).
+    pub fn intersect_ray(&self, e: &Vec3<T>, d: &Vec3<T>, t0: T, t1: T) -> Option<(T, T, T)> {
+        let (ta, tb, tc) = (&self.a, &self.b, &self.c);
+        let ((a, b, c), (d, e, f), (g, h, i), (j, k, l)) = (
+            (ta.x - tb.x, ta.y - tb.y, ta.z - tb.z),
+            (ta.x - tc.x, ta.y - tc.y, ta.z - tc.z),
+            (d.x, d.y, d.z),
+            (ta.x - e.x, ta.y - e.y, ta.z - e.z),
+        );
+
+        let ei_hf = e * i - h * f;
+        let gf_di = g * f - d * i;
+        let dh_eg = d * h - e * g;
+        let ak_jb = a * k - j * b;
+        let jc_al = j * c - a * l;
+        let bl_kc = b * l - k * c;
+
+        let m = a * ei_hf + b * gf_di + c * dh_eg;
+
+        let t = -(f * ak_jb + e * jc_al + d * bl_kc) / m;
+        if !(t0..=t1).contains(&t) {
+            return None;
+        }
+
+        let gamma = (i * ak_jb + h * jc_al + g * bl_kc) / m;
+        if !(T::ZERO..=T::ONE).contains(&gamma) {
+            return None;
+        }
+
+        let beta = (j * ei_hf + k * gf_di + l * dh_eg) / m;
+        if !(T::ZERO..=T::ONE - gamma).contains(&beta) {
+            return None;
+        }
+
+        Some((t, beta, gamma))
+    }
+
+    /// Same as `intersect_ray` by the Möller–Trumbore algorithm, which reuses
+    /// two cross products and skips work as soon as a test fails.
+    pub fn intersect_ray_moller_trumbore(&self, e: &Vec3<T>, d: &Vec3<T>, t0: T, t1: T) -> Option<(T, T, T)> {
+        let e1 = &self.b - &self.a;
+        let e2 = &self.c - &self.a;
+
+        let p = d.cross(&e2);
+        let det = e1.dot(&p);
+        if det == T::ZERO {
+            return None;
+        }
+        let inv_det = T::ONE / det;
+
+        let s = e - &self.a;
+        let beta = s.dot(&p) * inv_det;
+        if !(T::ZERO..=T::ONE).contains(&beta) {
+            return None;
+        }
+
+        let q = s.cross(&e1);
+        let gamma = d.dot(&q) * inv_det;
+        if gamma < T::ZERO || beta + gamma > T::ONE {
+            return None;
+        }
+
+        let t = e2.dot(&q) * inv_det;
+        (t0..=t1).contains(&t).then_some((t, beta, gamma))
+    }
+
+    /// Same as `intersect_ray` by the watertight algorithm of Woop, Benthin
+    /// and Wald (JCGT 2013). Rays through a shared edge or vertex hit at least
+    /// one of the triangles, which the other two variants cannot promise.
+    pub fn intersect_ray_watertight(&self, e: &Vec3<T>, d: &Vec3<T>, t0: T, t1: T) -> Option<(T, T, T)> {
+        let zero = T::ZERO;
+
+        // permute so that z is the dominant direction, keeping the winding
+        let kz = (0..3).max_by(|&i, &j| d[i].abs().to_f64().total_cmp(&d[j].abs().to_f64())).unwrap();
+        let (mut kx, mut ky) = ((kz + 1) % 3, (kz + 2) % 3);
+        if d[kz] < zero {
+            std::mem::swap(&mut kx, &mut ky);
+        }
+
+        // shear so that the ray runs along +z from the origin
+        let sx = d[kx] / d[kz];
+        let sy = d[ky] / d[kz];
+        let sz = T::ONE / d[kz];
+
+        let a = &self.a - e;
+        let b = &self.b - e;
+        let c = &self.c - e;
+        let (ax, ay) = (a[kx] - sx * a[kz], a[ky] - sy * a[kz]);
+        let (bx, by) = (b[kx] - sx * b[kz], b[ky] - sy * b[kz]);
+        let (cx, cy) = (c[kx] - sx * c[kz], c[ky] - sy * c[kz]);
+
+        // 2D edge functions, redone in double precision when one is exactly
+        // zero so the sign on shared edges is consistent
+        let (mut u, mut v, mut w) = (cx * by - cy * bx, ax * cy - ay * cx, bx * ay - by * ax);
+        if u == zero || v == zero || w == zero {
+            let [ax, ay, bx, by, cx, cy] = [ax, ay, bx, by, cx, cy].map(T::to_f64);
+            u = T::from_f64(cx * by - cy * bx);
+            v = T::from_f64(ax * cy - ay * cx);
+            w = T::from_f64(bx * ay - by * ax);
+        }
+
+        if (u < zero || v < zero || w < zero) && (u > zero || v > zero || w > zero) {
+            return None;
+        }
+
+        let det = u + v + w;
+        if det == zero {
+            return None;
+        }
+
+        let t = (u * sz * a[kz] + v * sz * b[kz] + w * sz * c[kz]) / det;
+        (t0..=t1).contains(&t).then_some((t, v / det, w / det))
+    }
+
+    /// Converts the corners to another precision, rounding when narrowing.
+    pub fn cast<U: Scalar>(&self) -> Tri<U> {
+        Tri {
+            a: self.a.cast(),
+            b: self.b.cast(),
+            c: self.c.cast(),
+        }
+    }
+}
+
+impl From<Triangle> for Triangled {
+    fn from(t: Triangle) -> Self {
+        t.cast()
+    }
+}
+
+#[cfg(test)]
+mod test_triangle {
+    use super::*;
+    use crate::math::{BoundingBox, Vector3, Vector3d};
+
+    fn assert_near(a: &Vector3, b: &Vector3) {
+        assert!((a - b).norm() < 1e-5, "{:?} != {:?}", a, b);
+    }
+
+    fn unit() -> Triangle {
+        Triangle::new(Vector3::new(1., 0., 0.), Vector3::new(0., 1., 0.), Vector3::new(0., 0., 1.))
+    }
+
+    #[test]
+    fn test_geometry() {
+        let t = Triangle::new(Vector3::new(0., 0., 0.), Vector3::new(2., 0., 0.), Vector3::new(0., 2., 0.));
+
+        assert_eq!(2., t.area());
+        assert_eq!(Vector3::new(0., 0., 1.), t.normal());
+        assert_eq!(BoundingBox::new(&Vector3::new(0., 0., 0.), &Vector3::new(2., 2., 0.)), t.bounding_box());
+
+        let (alpha, beta, gamma) = t.barycentric(&Vector3::new(0.5, 1., 3.));
+        assert!((alpha - 0.25).abs() < 1e-6 && (beta - 0.25).abs() < 1e-6 && (gamma - 0.5).abs() < 1e-6);
+        assert_near(&Vector3::new(0.5, 1., 0.), &t.point(beta, gamma));
+
+        let (alpha, beta, gamma) = t.barycentric(&t.centroid());
+        assert!((alpha - beta).abs() < 1e-6 && (beta - gamma).abs() < 1e-6);
+
+        // outside beyond the hypotenuse
+        assert!(t.barycentric(&Vector3::new(2., 2., 0.)).0 < 0.);
+    }
+
+    #[test]
+    fn test_closest_point() {
+        let t = Triangle::new(Vector3::new(0., 0., 0.), Vector3::new(2., 0., 0.), Vector3::new(0., 2., 0.));
+
+        // one point in each Voronoi region
+        assert_eq!(Vector3::new(0.5, 0.5, 0.), t.closest_point(&Vector3::new(0.5, 0.5, 4.)));
+        assert_eq!(Vector3::new(0., 0., 0.), t.closest_point(&Vector3::new(-1., -1., 1.)));
+        assert_eq!(Vector3::new(2., 0., 0.), t.closest_point(&Vector3::new(3., -1., 0.)));
+        assert_eq!(Vector3::new(0., 2., 0.), t.closest_point(&Vector3::new(-1., 3., 0.)));
+        assert_eq!(Vector3::new(1., 0., 0.), t.closest_point(&Vector3::new(1., -2., 1.)));
+        assert_eq!(Vector3::new(0., 1., 0.), t.closest_point(&Vector3::new(-2., 1., 0.)));
+        assert_near(&Vector3::new(1., 1., 0.), &t.closest_point(&Vector3::new(2., 2., -1.)));
+    }
+
+    #[test]
+    fn test_intersect_ray() {
+        let t = unit();
+        let e = Vector3::new(1., 1., 1.);
+
+        for target in [
+            Vector3::new(1. / 3., 1. / 3., 1. / 3.),
+            Vector3::new(0.2, 0.3, 0.5),
+            Vector3::new(0.8, 0.1, 0.1),
+            Vector3::new(1., 1., -1.),
+            Vector3::new(-0.5, 0.5, 1.),
+        ] {
+            let d = &target - &e;
+            let hits = [
+                t.intersect_ray(&e, &d, 0., 2.),
+                t.intersect_ray_moller_trumbore(&e, &d, 0., 2.),
+                t.intersect_ray_watertight(&e, &d, 0., 2.),
+            ];
+            let expected = t.barycentric(&target);
+            let inside = expected.0 >= 0. && expected.1 >= 0. && expected.2 >= 0.;
+
+            for hit in hits {
+                assert_eq!(inside, hit.is_some(), "{:?} {:?}", target, hit);
+                if let Some((t, beta, gamma)) = hit {
+                    assert!((t - 1.).abs() < 1e-5);
+                    assert!((beta - expected.1).abs() < 1e-5 && (gamma - expected.2).abs() < 1e-5);
+                    assert_near(&target, &(&e + &(t * &d)));
+                }
+            }
+        }
+
+        // parallel, behind and out of range
+        let d = Vector3::new(1., -1., 0.);
+        assert_eq!(None, t.intersect_ray_moller_trumbore(&e, &d, 0., 10.));
+        assert_eq!(None, t.intersect_ray_watertight(&e, &d, 0., 10.));
+        let d = Vector3::new(1., 1., 1.);
+        assert_eq!(None, t.intersect_ray(&e, &d, 0., 10.));
+        assert_eq!(None, t.intersect_ray_moller_trumbore(&e, &d, 0., 10.));
+        assert_eq!(None, t.intersect_ray_watertight(&e, &d, 0., 10.));
+        assert_eq!(None, t.intersect_ray_watertight(&e, &-&d, 0., 0.5));
+    }
+
+    #[test]
+    fn test_watertight() {
+        // a skewed quad split along its diagonal, hit by rays aimed exactly at
+        // points on the shared edge
+        let (p, q) = (Vector3::new(0.1, 0.7, 0.3), Vector3::new(2.3, 1.9, 0.4));
+        let first = Triangle::new(p, Vector3::new(2.1, 0.2, 0.1), q);
+        let second = Triangle::new(p, q, Vector3::new(-0.4, 2.2, 0.6));
+        let e = Vector3::new(0.3, 0.9, 5.);
+
+        let targets = (1..64).map(|i| &p + &((i as f32 / 64.) * &(&q - &p)));
+        for (i, target) in [p, q].into_iter().chain(targets).enumerate() {
+            let d = &target - &e;
+
+            let hit = |t: &Triangle| t.intersect_ray_watertight(&e, &d, 0., 2.);
+            assert!(hit(&first).is_some() || hit(&second).is_some(), "{i}");
+        }
+    }
+
+    #[test]
+    fn test_precision() {
+        let t = unit();
+        assert_eq!(t, Triangled::from(t).cast());
+
+        // a sliver far from the origin: in f32 the system is singular and
+        // Cramer's rule divides 0 by 0
+        let t = Triangled::new(Vector3d::new(1e4, 0., 0.), Vector3d::new(1e4 + 1e-4, 0., 0.), Vector3d::new(1e4, 1e-4, 0.));
+        let (e, d) = (Vector3d::new(1e4 + 2e-5, 2e-5, 1.), Vector3d::new(0., 0., -1.));
+
+        let (t_hit, beta, gamma) = t.intersect_ray(&e, &d, 0., 2.).unwrap();
+        assert!((t_hit - 1.).abs() < 1e-9 && (beta - 0.2).abs() < 1e-6 && (gamma - 0.2).abs() < 1e-6);
+        assert_eq!(None, t.cast::<f32>().intersect_ray(&e.cast(), &d.cast(), 0., 2.));
+    }
+}
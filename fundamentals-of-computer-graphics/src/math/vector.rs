@@ -0,0 +1,293 @@
+use crate::math::{Scalar, Vec3};
+use bytemuck::{Pod, Zeroable};
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+#[repr(C)]
+#[derive(PartialOrd, PartialEq, Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Vec2<T> {
+    pub x: T,
+    pub y: T,
+}
+
+/// Homogeneous coordinate `(x, y, z, w)`.
+#[repr(C)]
+#[derive(PartialOrd, PartialEq, Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Vec4<T> {
+    pub x: T,
+    pub y: T,
+    pub z: T,
+    pub w: T,
+}
+
+pub type Vector2 = Vec2<f32>;
+pub type Vector2d = Vec2<f64>;
+pub type Vector4 = Vec4<f32>;
+pub type Vector4d = Vec4<f64>;
+
+// SAFETY: repr(C) with fields of a single Pod type has no padding.
+unsafe impl<T: Scalar> Zeroable for Vec2<T> {}
+unsafe impl<T: Scalar> Pod for Vec2<T> {}
+unsafe impl<T: Scalar> Zeroable for Vec4<T> {}
+unsafe impl<T: Scalar> Pod for Vec4<T> {}
+
+impl<T: Scalar> Vec2<T> {
+    pub fn new(x: T, y: T) -> Self {
+        Self { x, y }
+    }
+
+    /// The z component of the 3D cross product, positive when `rhs` is
+    /// counter-clockwise from `self`.
+    pub fn cross(&self, rhs: &Self) -> T {
+        self.x * rhs.y - self.y * rhs.x
+    }
+
+    pub fn extend(&self, z: T) -> Vec3<T> {
+        Vec3::new(self.x, self.y, z)
+    }
+}
+
+impl<T: Scalar> Vec4<T> {
+    pub fn new(x: T, y: T, z: T, w: T) -> Self {
+        Self { x, y, z, w }
+    }
+
+    /// Drops w without dividing, e.g. for directions.
+    pub fn truncate(&self) -> Vec3<T> {
+        Vec3::new(self.x, self.y, self.z)
+    }
+
+    /// Perspective divide back to a 3D point.
+    pub fn project(&self) -> Vec3<T> {
+        Vec3::new(self.x / self.w, self.y / self.w, self.z / self.w)
+    }
+}
+
+impl<T: Scalar> Vec3<T> {
+    pub fn extend(&self, w: T) -> Vec4<T> {
+        Vec4::new(self.x, self.y, self.z, w)
+    }
+
+    pub fn truncate(&self) -> Vec2<T> {
+        Vec2::new(self.x, self.y)
+    }
+}
+
+pub fn vec2(x: f32, y: f32) -> Vector2 {
+    Vector2::new(x, y)
+}
+
+pub fn vec4(x: f32, y: f32, z: f32, w: f32) -> Vector4 {
+    Vector4::new(x, y, z, w)
+}
+
+impl<T: Scalar> From<[T; 2]> for Vec2<T> {
+    fn from([x, y]: [T; 2]) -> Self {
+        Self { x, y }
+    }
+}
+
+impl<T: Scalar> From<Vec2<T>> for [T; 2] {
+    fn from(v: Vec2<T>) -> Self {
+        [v.x, v.y]
+    }
+}
+
+impl<T: Scalar> From<[T; 4]> for Vec4<T> {
+    fn from([x, y, z, w]: [T; 4]) -> Self {
+        Self { x, y, z, w }
+    }
+}
+
+impl<T: Scalar> From<Vec4<T>> for [T; 4] {
+    fn from(v: Vec4<T>) -> Self {
+        [v.x, v.y, v.z, v.w]
+    }
+}
+
+/// Component-wise operators by reference, mirroring the hand-written ones on
+/// `Vec3`, plus `dot`, `norm`, `normalize` and precision conversions.
+macro_rules! impl_vector {
+    ($v:ident { $($c:ident),+ }) => {
+        impl<T: Scalar> $v<T> {
+            pub fn normalize(&self) -> Self {
+                self / self.norm()
+            }
+
+            pub fn norm(&self) -> T {
+                self.dot(self).sqrt()
+            }
+
+            pub fn dot(&self, rhs: &Self) -> T {
+                T::ZERO $(+ self.$c * rhs.$c)+
+            }
+
+            /// Converts the components to another precision, rounding when
+            /// narrowing.
+            pub fn cast<U: Scalar>(&self) -> $v<U> {
+                $v { $($c: U::from_f64(self.$c.to_f64())),+ }
+            }
+        }
+
+        impl From<$v<f32>> for $v<f64> {
+            fn from(v: $v<f32>) -> Self {
+                v.cast()
+            }
+        }
+
+        impl<T: Scalar> Div<T> for &$v<T> {
+            type Output = $v<T>;
+
+            fn div(self, rhs: T) -> Self::Output {
+                $v { $($c: self.$c / rhs),+ }
+            }
+        }
+
+        impl Mul<&$v<f32>> for f32 {
+            type Output = $v<f32>;
+
+            fn mul(self, rhs: &$v<f32>) -> Self::Output {
+                $v { $($c: self * rhs.$c),+ }
+            }
+        }
+
+        impl Mul<&$v<f64>> for f64 {
+            type Output = $v<f64>;
+
+            fn mul(self, rhs: &$v<f64>) -> Self::Output {
+                $v { $($c: self * rhs.$c),+ }
+            }
+        }
+
+        impl<T: Scalar> Mul<T> for &$v<T> {
+            type Output = $v<T>;
+
+            fn mul(self, rhs: T) -> Self::Output {
+                $v { $($c: self.$c * rhs),+ }
+            }
+        }
+
+        impl<T: Scalar> Neg for &$v<T> {
+            type Output = $v<T>;
+
+            fn neg(self) -> Self::Output {
+                $v { $($c: -self.$c),+ }
+            }
+        }
+
+        impl<T: Scalar> Add<&$v<T>> for &$v<T> {
+            type Output = $v<T>;
+
+            fn add(self, rhs: &$v<T>) -> Self::Output {
+                $v { $($c: self.$c + rhs.$c),+ }
+            }
+        }
+
+        impl<T: Scalar> Sub<&$v<T>> for &$v<T> {
+            type Output = $v<T>;
+
+            fn sub(self, rhs: &$v<T>) -> Self::Output {
+                $v { $($c: self.$c - rhs.$c),+ }
+            }
+        }
+
+        impl<T: Scalar> Mul<&$v<T>> for &$v<T> {
+            type Output = T;
+
+            fn mul(self, rhs: &$v<T>) -> Self::Output {
+                self.dot(rhs)
+            }
+        }
+    };
+}
+
+impl_vector!(Vec2 { x, y });
+impl_vector!(Vec4 { x, y, z, w });
+
+#[cfg(test)]
+mod test_vector2 {
+    use super::*;
+    use crate::math::Vector3;
+
+    #[test]
+    fn test_ops() {
+        let a = vec2(1., 2.);
+        let b = vec2(3., 5.);
+
+        assert_eq!(vec2(4., 7.), &a + &b);
+        assert_eq!(vec2(-2., -3.), &a - &b);
+        assert_eq!(vec2(-1., -2.), -&a);
+        assert_eq!(vec2(2., 4.), &a * 2.);
+        assert_eq!(vec2(2., 4.), 2. * &a);
+        assert_eq!(vec2(0.5, 1.), &a / 2.);
+        assert_eq!(13., a.dot(&b));
+        assert_eq!(13., &b * &a);
+    }
+
+    #[test]
+    fn test_cross() {
+        let x = vec2(1., 0.);
+        let y = vec2(0., 1.);
+
+        assert_eq!(1., x.cross(&y));
+        assert_eq!(-1., y.cross(&x));
+        assert_eq!(0., x.cross(&x));
+    }
+
+    #[test]
+    fn test_norm() {
+        assert_eq!(5., vec2(3., 4.).norm());
+        assert_eq!(vec2(0.6, 0.8), vec2(3., 4.).normalize());
+    }
+
+    #[test]
+    fn test_conversions() {
+        assert_eq!(Vector3::new(1., 2., 3.), vec2(1., 2.).extend(3.));
+        assert_eq!(vec2(1., 2.), Vector3::new(1., 2., 3.).truncate());
+        assert_eq!(vec2(1., 2.), Vector2::from([1., 2.]));
+        assert_eq!([1., 2.], <[f32; 2]>::from(vec2(1., 2.)));
+        assert_eq!(Vector2d::new(0.1f32 as f64, 2.), Vector2d::from(vec2(0.1, 2.)));
+        assert_eq!(vec2(0.1, 2.), Vector2d::new(0.1, 2.).cast());
+    }
+}
+
+#[cfg(test)]
+mod test_vector4 {
+    use super::*;
+    use crate::math::Vector3;
+
+    #[test]
+    fn test_ops() {
+        let a = vec4(1., 2., 3., 4.);
+        let b = vec4(4., 3., 2., 1.);
+
+        assert_eq!(vec4(5., 5., 5., 5.), &a + &b);
+        assert_eq!(vec4(-3., -1., 1., 3.), &a - &b);
+        assert_eq!(vec4(-1., -2., -3., -4.), -&a);
+        assert_eq!(vec4(2., 4., 6., 8.), &a * 2.);
+        assert_eq!(vec4(2., 4., 6., 8.), 2. * &a);
+        assert_eq!(vec4(0.5, 1., 1.5, 2.), &a / 2.);
+        assert_eq!(20., a.dot(&b));
+        assert_eq!(20., &b * &a);
+        assert_eq!(30f32.sqrt(), a.norm());
+    }
+
+    #[test]
+    fn test_homogeneous() {
+        let p = Vector3::new(1., 2., 3.);
+
+        assert_eq!(vec4(1., 2., 3., 1.), p.extend(1.));
+        assert_eq!(p, p.extend(1.).project());
+        assert_eq!(p, p.extend(0.).truncate());
+        assert_eq!(p, vec4(2., 4., 6., 2.).project());
+    }
+
+    #[test]
+    fn test_conversions() {
+        assert_eq!(vec4(1., 2., 3., 4.), Vector4::from([1., 2., 3., 4.]));
+        assert_eq!([1., 2., 3., 4.], <[f32; 4]>::from(vec4(1., 2., 3., 4.)));
+        assert_eq!(Vector4d::new(1., 2., 3., 0.1f32 as f64), Vector4d::from(vec4(1., 2., 3., 0.1)));
+        assert_eq!(vec4(1., 2., 3., 0.1), Vector4d::new(1., 2., 3., 0.1).cast());
+    }
+}
@@ -0,0 +1,191 @@
+use crate::math::{Mat4, Scalar, Vec3, Xform};
+
+/// Viewing matrices following FCG chapter 7: the camera looks down -z, so
+/// the near and far planes have `0 > n > f`, and the canonical view volume
+/// is `[-1, 1]^3` with the near plane at z = 1.
+impl<T: Scalar> Mat4<T> {
+    /// M_vp: canonical view volume to an `nx` by `ny` image, pixel centers
+    /// at integer coordinates.
+    pub fn viewport(nx: u32, ny: u32) -> Self {
+        let (o, l, h) = (T::ZERO, T::ONE, T::HALF);
+        let (nx, ny) = (T::from_f64(nx as f64), T::from_f64(ny as f64));
+        Self::from_rows([
+            [nx * h, o, o, (nx - l) * h],
+            [o, ny * h, o, (ny - l) * h],
+            [o, o, l, o],
+            [o, o, o, l],
+        ])
+    }
+
+    /// M_orth: the box `[l, r] x [b, t] x [f, n]` to the canonical view
+    /// volume.
+    pub fn orthographic(l: T, r: T, b: T, t: T, n: T, f: T) -> Self {
+        let (o, one, two) = (T::ZERO, T::ONE, T::TWO);
+        Self::from_rows([
+            [two / (r - l), o, o, -(r + l) / (r - l)],
+            [o, two / (t - b), o, -(t + b) / (t - b)],
+            [o, o, two / (n - f), -(n + f) / (n - f)],
+            [o, o, o, one],
+        ])
+    }
+
+    /// P: the perspective matrix that squashes the frustum into a box,
+    /// leaving the near and far planes in place.
+    pub fn perspective(n: T, f: T) -> Self {
+        let (o, one) = (T::ZERO, T::ONE);
+        Self::from_rows([
+            [n, o, o, o],
+            [o, n, o, o],
+            [o, o, n + f, -f * n],
+            [o, o, one, o],
+        ])
+    }
+
+    /// M_per = M_orth P, for the frustum whose near plane spans
+    /// `[l, r] x [b, t]`.
+    pub fn perspective_projection(l: T, r: T, b: T, t: T, n: T, f: T) -> Self {
+        let (o, one, two) = (T::ZERO, T::ONE, T::TWO);
+        Self::from_rows([
+            [two * n / (r - l), o, (l + r) / (l - r), o],
+            [o, two * n / (t - b), (b + t) / (b - t), o],
+            [o, o, (f + n) / (n - f), two * f * n / (f - n)],
+            [o, o, one, o],
+        ])
+    }
+
+    /// M_per for a symmetric frustum with vertical field of view `fovy`
+    /// (radians) and `aspect` = width / height.
+    pub fn perspective_fov(fovy: T, aspect: T, n: T, f: T) -> Self {
+        let t = n.abs() * (fovy * T::HALF).tan();
+        let r = t * aspect;
+        Self::perspective_projection(-r, r, -t, t, n, f)
+    }
+
+    /// M_cam: world to camera coordinates for a camera at `eye` looking
+    /// along `gaze` with `up` roughly above.
+    pub fn camera(eye: &Vec3<T>, gaze: &Vec3<T>, up: &Vec3<T>) -> Self {
+        *Xform::look_at(eye, &(eye + gaze), up).matrix()
+    }
+}
+
+#[cfg(test)]
+mod test_viewing {
+    use crate::math::{Matrix4, Matrix4d, Vector3, Vector3d};
+    use std::f32::consts::FRAC_PI_2;
+
+    fn assert_near(a: &Vector3, b: &Vector3) {
+        assert!((a - b).norm() < 1e-3, "{:?} != {:?}", a, b);
+    }
+
+    #[test]
+    fn test_viewport() {
+        let m = Matrix4::viewport(200, 100);
+
+        assert_near(&Vector3::new(-0.5, -0.5, 0.), &m.transform_point(&Vector3::new(-1., -1., 0.)));
+        assert_near(&Vector3::new(199.5, 99.5, 0.), &m.transform_point(&Vector3::new(1., 1., 0.)));
+        assert_near(&Vector3::new(99.5, 49.5, 0.), &m.transform_point(&Vector3::new(0., 0., 0.)));
+    }
+
+    #[test]
+    fn test_orthographic() {
+        let m = Matrix4::orthographic(-2., 4., -1., 1., -1., -11.);
+
+        assert_near(&Vector3::new(-1., -1., 1.), &m.transform_point(&Vector3::new(-2., -1., -1.)));
+        assert_near(&Vector3::new(1., 1., -1.), &m.transform_point(&Vector3::new(4., 1., -11.)));
+        assert_near(&Vector3::new(0., 0., 0.), &m.transform_point(&Vector3::new(1., 0., -6.)));
+    }
+
+    #[test]
+    fn test_perspective() {
+        let (n, f) = (-1., -10.);
+        let p = Matrix4::perspective(n, f);
+
+        // points on the near and far planes stay on them
+        let near = Vector3::new(0.5, 0.25, n);
+        assert_near(&near, &p.transform_point(&near));
+        assert_near(&Vector3::new(0.5, 0.25, f), &p.transform_point(&Vector3::new(5., 2.5, f)));
+
+        // x and y shrink in proportion to 1 / z
+        let q = p.transform_point(&Vector3::new(4., 2., -4.));
+        assert_near(&Vector3::new(1., 0.5, q.z), &q);
+        assert!(q.z < n && q.z > f);
+
+        let m = Matrix4::perspective_projection(-1., 1., -1., 1., n, f);
+        let expected = &Matrix4::orthographic(-1., 1., -1., 1., n, f) * &p;
+        for c in 0..4 {
+            for r in 0..4 {
+                assert!((expected[(r, c)] - m[(r, c)]).abs() < 1e-5);
+            }
+        }
+    }
+
+    #[test]
+    fn test_perspective_fov() {
+        // 90° with aspect 2 makes the near plane [-2, 2] x [-1, 1] at z = -1
+        let m = Matrix4::perspective_fov(FRAC_PI_2, 2., -1., -100.);
+
+        assert_near(&Vector3::new(1., 1., 1.), &m.transform_point(&Vector3::new(2., 1., -1.)));
+        assert_near(&Vector3::new(-1., -1., -1.), &m.transform_point(&Vector3::new(-200., -100., -100.)));
+    }
+
+    #[test]
+    fn test_camera() {
+        let eye = Vector3::new(1., 2., 3.);
+        let m = Matrix4::camera(&eye, &Vector3::new(0., 0., -1.), &Vector3::new(0., 1., 0.));
+
+        assert_near(&Vector3::new(0., 0., 0.), &m.transform_point(&eye));
+        assert_near(&Vector3::new(0., 0., -1.), &m.transform_point(&Vector3::new(1., 2., 2.)));
+
+        // looking along +x, the world's +z is to the right
+        let m = Matrix4::camera(&eye, &Vector3::new(1., 0., 0.), &Vector3::new(0., 1., 0.));
+        assert_near(&Vector3::new(-1., 0., -1.), &m.transform_point(&Vector3::new(2., 2., 2.)));
+    }
+
+    #[test]
+    fn test_pipeline() {
+        let (nx, ny) = (300, 200);
+        let (n, f) = (-1., -50.);
+        let eye = Vector3::new(0., 1., 5.);
+        let gaze = Vector3::new(0., 0., -1.);
+        let up = Vector3::new(0., 1., 0.);
+
+        let m = &Matrix4::viewport(nx, ny)
+            * &(&Matrix4::perspective_fov(FRAC_PI_2, 1.5, n, f) * &Matrix4::camera(&eye, &gaze, &up));
+
+        // the point straight ahead lands in the image center
+        let center = m.transform_point(&Vector3::new(0., 1., 0.));
+        assert_near(&Vector3::new(149.5, 99.5, center.z), &center);
+
+        // near-plane corners land on the image corners
+        let corner = m.transform_point(&Vector3::new(1.5, 2., 4.));
+        assert_near(&Vector3::new(299.5, 199.5, 1.), &corner);
+        let corner = m.transform_point(&Vector3::new(-1.5, 0., 4.));
+        assert_near(&Vector3::new(-0.5, -0.5, 1.), &corner);
+
+        // farther points have smaller depth
+        let a = m.transform_point(&Vector3::new(0., 1., -5.));
+        let b = m.transform_point(&Vector3::new(0., 1., -10.));
+        assert!(center.z > a.z && a.z > b.z);
+
+        // and the whole chain inverts back to world space
+        let inv = m.inverse().unwrap();
+        for p in [Vector3::new(0.3, -0.2, -3.), Vector3::new(-4., 2., -20.), Vector3::new(1., 1., 2.)] {
+            assert_near(&p, &inv.transform_point(&m.transform_point(&p)));
+        }
+    }
+
+    #[test]
+    fn test_precision() {
+        // the same pipeline in f64, for a scene far from the origin where f32
+        // steps are larger than the geometry
+        let (n, f) = (-1., -50.);
+        let eye = Vector3d::new(1e8, 1e8, 1e8);
+        let m = &Matrix4d::perspective_fov(std::f64::consts::FRAC_PI_2, 1., n, f)
+            * &Matrix4d::camera(&eye, &Vector3d::new(0., 0., -1.), &Vector3d::new(0., 1., 0.));
+
+        let p = &eye + &Vector3d::new(0.25, 0.5, -2.);
+        let q = m.transform_point(&p);
+        assert!((q.x - 0.125).abs() < 1e-9 && (q.y - 0.25).abs() < 1e-9);
+        assert_eq!(Matrix4d::viewport(4, 2), Matrix4::viewport(4, 2).into());
+    }
+}
@@ -0,0 +1,29 @@
+use crate::math::Vector3;
+
+/// Indexed triangle mesh with a normal per vertex. Triangles are counter-
+/// clockwise when seen from outside.
+#[derive(PartialEq, Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Mesh {
+    pub positions: Vec<Vector3>,
+    pub normals: Vec<Vector3>,
+    pub triangles: Vec<[usize; 3]>,
+}
+
+impl Mesh {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a vertex and returns its index.
+    pub fn push_vertex(&mut self, position: Vector3, normal: Vector3) -> usize {
+        self.positions.push(position);
+        self.normals.push(normal);
+        self.positions.len() - 1
+    }
+
+    /// Corner positions of triangle `i`.
+    pub fn triangle(&self, i: usize) -> [Vector3; 3] {
+        self.triangles[i].map(|v| self.positions[v])
+    }
+}
@@ -0,0 +1,347 @@
+//! Software rasterization into a `Framebuffer`, following FCG chapter 8.
+
+use crate::color::Color;
+use crate::math::{Vector2, Vector3};
+
+/// RGBA8 color and depth buffer. Pixel `(x, y)` has its center at integer
+/// coordinates with y pointing up, matching `Matrix4::viewport`, while rows
+/// are stored top to bottom so `as_bytes` can be handed to a canvas
+/// `ImageData` or a texture upload as is.
+///
+/// Depth follows FCG's convention of looking down -z: larger z is nearer.
+#[derive(PartialEq, Debug, Clone)]
+pub struct Framebuffer {
+    width: usize,
+    height: usize,
+    pixels: Vec<[u8; 4]>,
+    depth: Vec<f32>,
+}
+
+impl Framebuffer {
+    /// Transparent black, with every depth at the far end.
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![[0; 4]; width * height],
+            depth: vec![f32::NEG_INFINITY; width * height],
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Fills every pixel with opaque `color` and resets the depth.
+    pub fn clear(&mut self, color: &Color) {
+        self.pixels.fill(rgba8(color));
+        self.depth.fill(f32::NEG_INFINITY);
+    }
+
+    fn index(&self, x: i64, y: i64) -> Option<usize> {
+        let (w, h) = (self.width as i64, self.height as i64);
+        ((0..w).contains(&x) && (0..h).contains(&y)).then(|| ((h - 1 - y) * w + x) as usize)
+    }
+
+    pub fn pixel(&self, x: usize, y: usize) -> [u8; 4] {
+        self.pixels[self.index(x as i64, y as i64).expect("pixel out of bounds")]
+    }
+
+    pub fn depth(&self, x: usize, y: usize) -> f32 {
+        self.depth[self.index(x as i64, y as i64).expect("pixel out of bounds")]
+    }
+
+    /// Writes an opaque pixel, ignoring coordinates outside the image.
+    pub fn set_pixel(&mut self, x: i64, y: i64, color: &Color) {
+        if let Some(i) = self.index(x, y) {
+            self.pixels[i] = rgba8(color);
+        }
+    }
+
+    /// RGBA bytes, rows top to bottom.
+    pub fn as_bytes(&self) -> &[u8] {
+        bytemuck::cast_slice(&self.pixels)
+    }
+
+    /// Draws the line between the pixels nearest to `p0` and `p1` with the
+    /// midpoint algorithm (FCG 8.1.1), one pixel per column or per row,
+    /// whichever is longer. The line is first clipped to a pixel beyond the
+    /// image, so far away endpoints cost no more than the visible part.
+    pub fn draw_line(&mut self, p0: &Vector2, p1: &Vector2, color: &Color) {
+        let max = Vector2::new(self.width as f32, self.height as f32);
+        let Some((p0, p1)) = clip_line(p0, p1, &Vector2::new(-1., -1.), &max) else { return };
+
+        let (mut x0, mut y0) = (p0.x.round() as i64, p0.y.round() as i64);
+        let (mut x1, mut y1) = (p1.x.round() as i64, p1.y.round() as i64);
+
+        // walk along x, transposing steep lines
+        let steep = (y1 - y0).abs() > (x1 - x0).abs();
+        if steep {
+            (x0, y0, x1, y1) = (y0, x0, y1, x1);
+        }
+        if x0 > x1 {
+            (x0, y0, x1, y1) = (x1, y1, x0, y0);
+        }
+
+        let (dx, dy) = (x1 - x0, (y1 - y0).abs());
+        let step = if y1 > y0 { 1 } else { -1 };
+
+        // twice f at the midpoint (x + 1, y ± 1/2), kept in integers
+        let mut d = 2 * dy - dx;
+        let mut y = y0;
+        for x in x0..=x1 {
+            if steep {
+                self.set_pixel(y, x, color);
+            } else {
+                self.set_pixel(x, y, color);
+            }
+
+            if d > 0 {
+                y += step;
+                d -= 2 * dx;
+            }
+            d += 2 * dy;
+        }
+    }
+
+    /// Fills the triangle with corners `v` in screen space, interpolating
+    /// `colors` and the depth `z` by barycentric coordinates (FCG 8.1.2).
+    /// Fragments farther than what is already stored are discarded.
+    pub fn draw_triangle(&mut self, v: &[Vector3; 3], colors: &[Color; 3]) {
+        rasterize(v, (self.width, self.height), |x, y, [alpha, beta, gamma]| {
+            let Some(i) = self.index(x, y) else { return };
+
+            let z = alpha * v[0].z + beta * v[1].z + gamma * v[2].z;
+            if z > self.depth[i] {
+                self.depth[i] = z;
+                self.pixels[i] = rgba8(&(&(&(alpha * &colors[0]) + &(beta * &colors[1])) + &(gamma * &colors[2])));
+            }
+        });
+    }
+}
+
+/// The part of the segment `p0 p1` inside the rectangle `[min, max]`
+/// (Liang–Barsky), if any.
+fn clip_line(p0: &Vector2, p1: &Vector2, min: &Vector2, max: &Vector2) -> Option<(Vector2, Vector2)> {
+    let d = p1 - p0;
+    let (mut t0, mut t1) = (0f32, 1f32);
+
+    // p t <= q for each side, with p < 0 where the segment enters
+    for (p, q) in [(-d.x, p0.x - min.x), (d.x, max.x - p0.x), (-d.y, p0.y - min.y), (d.y, max.y - p0.y)] {
+        if p == 0. {
+            if q < 0. {
+                return None;
+            }
+        } else if p < 0. {
+            t0 = t0.max(q / p);
+        } else {
+            t1 = t1.min(q / p);
+        }
+    }
+
+    (t0 <= t1).then(|| (p0 + &(t0 * &d), p0 + &(t1 * &d)))
+}
+
+fn rgba8(color: &Color) -> [u8; 4] {
+    let [r, g, b] = color.to_srgb8();
+    [r, g, b, 255]
+}
+
+/// Calls `fragment` with the barycentric coordinates of every pixel center
+/// covered by the triangle within a `width` by `height` image. Centers
+/// exactly on an edge belong to the triangle on the same side as the
+/// off-screen point (-1, -1), or (-1, -2) for edges through it, so triangles
+/// sharing an edge cover each of its pixels once.
+fn rasterize(v: &[Vector3; 3], (width, height): (usize, usize), mut fragment: impl FnMut(i64, i64, [f32; 3])) {
+    // the implicit line through a and b, f(x, y) = 0
+    let f = |a: &Vector3, b: &Vector3, x: f32, y: f32| (a.y - b.y) * x + (b.x - a.x) * y + a.x * b.y - b.x * a.y;
+    let [a, b, c] = v;
+
+    let f_alpha = f(b, c, a.x, a.y);
+    let f_beta = f(c, a, b.x, b.y);
+    let f_gamma = f(a, b, c.x, c.y);
+    if f_alpha == 0. {
+        return;
+    }
+
+    // there is no clip stage, so only the bounding box on screen is scanned
+    let x_min = (a.x.min(b.x).min(c.x).ceil() as i64).max(0);
+    let x_max = (a.x.max(b.x).max(c.x).floor() as i64).min(width as i64 - 1);
+    let y_min = (a.y.min(b.y).min(c.y).ceil() as i64).max(0);
+    let y_max = (a.y.max(b.y).max(c.y).floor() as i64).min(height as i64 - 1);
+
+    let owns = |f_vertex: f32, p: &Vector3, q: &Vector3| {
+        let off = f(p, q, -1., -1.);
+        f_vertex * if off != 0. { off } else { f(p, q, -1., -2.) } > 0.
+    };
+    let (owns_bc, owns_ca, owns_ab) = (owns(f_alpha, b, c), owns(f_beta, c, a), owns(f_gamma, a, b));
+
+    for y in y_min..=y_max {
+        for x in x_min..=x_max {
+            let (px, py) = (x as f32, y as f32);
+            let alpha = f(b, c, px, py) / f_alpha;
+            let beta = f(c, a, px, py) / f_beta;
+            let gamma = f(a, b, px, py) / f_gamma;
+
+            if alpha >= 0.
+                && beta >= 0.
+                && gamma >= 0.
+                && (alpha > 0. || owns_bc)
+                && (beta > 0. || owns_ca)
+                && (gamma > 0. || owns_ab)
+            {
+                fragment(x, y, [alpha, beta, gamma]);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_rasterization {
+    use super::*;
+    use crate::math::{vec2, vec3};
+
+    fn lit(fb: &Framebuffer) -> Vec<(usize, usize)> {
+        let mut pixels = vec![];
+        for y in 0..fb.height() {
+            for x in 0..fb.width() {
+                if fb.pixel(x, y)[3] != 0 {
+                    pixels.push((x, y));
+                }
+            }
+        }
+        pixels
+    }
+
+    #[test]
+    fn test_line() {
+        for (p0, p1) in [
+            (vec2(1., 1.), vec2(14., 6.)),
+            (vec2(14., 6.), vec2(1., 1.)),
+            (vec2(2., 13.), vec2(5., 0.)),
+            (vec2(0., 0.), vec2(15., 15.)),
+            (vec2(3., 9.), vec2(12., 9.)),
+            (vec2(7., 2.), vec2(7., 2.)),
+        ] {
+            let mut fb = Framebuffer::new(16, 16);
+            fb.draw_line(&p0, &p1, &Color::WHITE);
+
+            // one pixel per step along the major axis, each next to the last
+            let pixels = lit(&fb);
+            let (dx, dy) = ((p1.x - p0.x).abs() as usize, (p1.y - p0.y).abs() as usize);
+            assert_eq!(dx.max(dy) + 1, pixels.len(), "{:?} {:?}", p0, p1);
+            assert!(pixels.contains(&(p0.x as usize, p0.y as usize)) && pixels.contains(&(p1.x as usize, p1.y as usize)));
+
+            // and never more than half a pixel off the line along the minor axis
+            let d = &p1 - &p0;
+            for &(x, y) in &pixels {
+                let (x, y) = (x as f32, y as f32);
+                let off = if d.x.abs() >= d.y.abs() && d.x != 0. {
+                    p0.y + (x - p0.x) / d.x * d.y - y
+                } else if d.y != 0. {
+                    p0.x + (y - p0.y) / d.y * d.x - x
+                } else {
+                    0.
+                };
+                assert!(off.abs() <= 0.5, "{:?} {:?}: {x}, {y}", p0, p1);
+            }
+        }
+
+        // clipped at the border
+        let mut fb = Framebuffer::new(4, 4);
+        fb.draw_line(&vec2(-5., 1.), &vec2(10., 1.), &Color::WHITE);
+        assert_eq!(4, lit(&fb).len());
+    }
+
+    #[test]
+    fn test_triangle() {
+        let mut fb = Framebuffer::new(16, 16);
+        let red = Color::new(1., 0., 0.);
+        let green = Color::new(0., 1., 0.);
+        let blue = Color::new(0., 0., 1.);
+        fb.draw_triangle(&[vec3(0., 0., 0.), vec3(15., 0., 0.), vec3(0., 15., 0.)], &[red, green, blue]);
+
+        assert_eq!(rgba8(&Color::new(13. / 15., 1. / 15., 1. / 15.)), fb.pixel(1, 1));
+        assert_eq!(rgba8(&Color::new(1. / 15., 13. / 15., 1. / 15.)), fb.pixel(13, 1));
+        assert_eq!(rgba8(&Color::new(7. / 15., 1. / 15., 7. / 15.)), fb.pixel(1, 7));
+        assert_eq!([0; 4], fb.pixel(15, 15));
+
+        // the edges on x = 0 and y = 0 face the off-screen point and belong to
+        // the neighbours across them, the diagonal is drawn
+        assert_eq!([0; 4], fb.pixel(0, 5));
+        assert_ne!([0; 4], fb.pixel(7, 8));
+        assert_eq!(14 * 15 / 2, lit(&fb).len());
+
+        // row 0 is the bottom one, stored last
+        assert_eq!(fb.pixel(1, 1), fb.as_bytes()[(14 * 16 + 1) * 4..(14 * 16 + 2) * 4]);
+    }
+
+    #[test]
+    fn test_shared_edges() {
+        // a fan of triangles around an interior point covers every pixel
+        // center exactly once, including those on shared edges and corners
+        let corners = [vec3(0., 0., 0.), vec3(12., 0., 0.), vec3(12., 12., 0.), vec3(0., 12., 0.)];
+        let center = vec3(6., 6., 0.);
+
+        let mut count = vec![0; 13 * 13];
+        for k in 0..4 {
+            rasterize(&[corners[k], corners[(k + 1) % 4], center], (13, 13), |x, y, _| count[(y * 13 + x) as usize] += 1);
+        }
+
+        // of the outer boundary only the sides away from (-1, -1) are drawn
+        for y in 0..13 {
+            for x in 0..13 {
+                let expected = if x == 0 || y == 0 { 0 } else { 1 };
+                assert_eq!(expected, count[y * 13 + x], "{x}, {y}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_far_off_screen() {
+        // vertices and endpoints far outside only cost the pixels on screen
+        let mut fb = Framebuffer::new(16, 16);
+        let v = [vec3(-1e6, -1e6, 0.), vec3(3e6, -1e6, 0.), vec3(-1e6, 3e6, 0.)];
+        fb.draw_triangle(&v, &[Color::WHITE; 3]);
+        assert_eq!(16 * 16, lit(&fb).len());
+
+        let mut fb = Framebuffer::new(16, 16);
+        fb.draw_line(&vec2(-1e7, 3.), &vec2(1e7, 3.), &Color::WHITE);
+        assert_eq!((0..16).map(|x| (x, 3)).collect::<Vec<_>>(), lit(&fb));
+
+        let mut fb = Framebuffer::new(16, 16);
+        fb.draw_line(&vec2(-1e6, -1e6), &vec2(1e6, 1e6), &Color::WHITE);
+        assert_eq!((0..16).map(|x| (x, x)).collect::<Vec<_>>(), lit(&fb));
+
+        // and lines missing the image draw nothing
+        let mut fb = Framebuffer::new(16, 16);
+        fb.draw_line(&vec2(-1e6, 20.), &vec2(1e6, 1e6), &Color::WHITE);
+        fb.draw_line(&vec2(-5., -3.), &vec2(30., -3.), &Color::WHITE);
+        assert!(lit(&fb).is_empty());
+    }
+
+    #[test]
+    fn test_depth() {
+        let near = [vec3(0., 0., 0.5), vec3(7., 0., 0.5), vec3(0., 7., 0.5)];
+        let far = [vec3(0., 0., -0.5), vec3(7., 0., -0.5), vec3(0., 7., -0.5)];
+        let (red, blue) = (Color::new(1., 0., 0.), Color::new(0., 0., 1.));
+
+        for order in [[near, far], [far, near]] {
+            let mut fb = Framebuffer::new(8, 8);
+            fb.clear(&Color::BLACK);
+            for v in order {
+                let color = if v == near { red } else { blue };
+                fb.draw_triangle(&v, &[color; 3]);
+            }
+
+            assert_eq!([255, 0, 0, 255], fb.pixel(1, 1));
+            assert_eq!(0.5, fb.depth(1, 1));
+            assert_eq!([0, 0, 0, 255], fb.pixel(7, 7));
+            assert_eq!(f32::NEG_INFINITY, fb.depth(7, 7));
+        }
+    }
+}
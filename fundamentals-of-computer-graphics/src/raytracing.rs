@@ -1,98 +1,646 @@
-use crate::math::{Vector3};
+use crate::color::Color;
+use crate::math::{self, BoundingBox, F32xN, MaskN, OrthonormalBasis, Scalar, Vec3, Vec3xN, Vector2, Vector3};
+use std::f32::consts::PI;
 
-struct Ray {
+/// The ray `e + t d`. `d` need not be unit length, so `t` measures distance
+/// in multiples of `|d|`.
+#[derive(PartialEq, Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Ray {
     pub e: Vector3,
     pub d: Vector3,
 }
 
 impl Ray {
+    pub fn new(e: Vector3, d: Vector3) -> Self {
+        Self { e, d }
+    }
+
     pub fn point(&self, t: f32) -> Vector3 {
         &self.e + &(t * &self.d)
     }
 }
 
+/// Where a ray hit a surface. `normal` is the unit geometric normal on the
+/// outside of the surface, whichever side the ray came from.
+#[derive(PartialEq, Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HitRecord {
+    pub t: f32,
+    pub point: Vector3,
+    pub normal: Vector3,
+    pub uv: Vector2,
+    pub material: usize,
+}
+
+/// Anything a ray can hit (FCG 4.4).
+pub trait Surface {
+    /// The nearest hit with `t` in `[t0, t1]`.
+    fn hit(&self, ray: &Ray, t0: f32, t1: f32) -> Option<HitRecord>;
+
+    /// Whether there is any hit with `t` in `[t0, t1]`, e.g. for shadow rays.
+    fn any_hit(&self, ray: &Ray, t0: f32, t1: f32) -> bool {
+        self.hit(ray, t0, t1).is_some()
+    }
+
+    fn bounding_box(&self) -> BoundingBox;
+}
+
+#[derive(PartialEq, Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Sphere {
+    pub center: Vector3,
+    pub radius: f32,
+    pub material: usize,
+}
+
+impl Sphere {
+    pub fn new(center: Vector3, radius: f32, material: usize) -> Self {
+        Self { center, radius, material }
+    }
+}
+
+impl Surface for Sphere {
+    /// Solves the quadratic in double precision, which f32 rounds to a root
+    /// at `t = 0` for rays leaving a sphere as large as a ground of radius
+    /// 5000. `uv` are the longitude and latitude of FCG 11.2.1, both in
+    /// `[0, 1]`.
+    fn hit(&self, ray: &Ray, t0: f32, t1: f32) -> Option<HitRecord> {
+        let (e, d, center) = (ray.e.cast::<f64>(), ray.d.cast::<f64>(), self.center.cast::<f64>());
+        let t = solve_sphere(&e, &d, &center, self.radius as f64, t0 as f64, t1 as f64)? as f32;
+        let r = self.radius;
+
+        let point = ray.point(t);
+        let normal = &(&point - &self.center) / r;
+        let theta = normal.z.clamp(-1., 1.).acos();
+        let phi = normal.y.atan2(normal.x);
+
+        Some(HitRecord {
+            t,
+            point,
+            normal,
+            uv: Vector2::new(phi.rem_euclid(2. * PI) / (2. * PI), (PI - theta) / PI),
+            material: self.material,
+        })
+    }
+
+    fn bounding_box(&self) -> BoundingBox {
+        let r = Vector3::new(self.radius, self.radius, self.radius);
+        BoundingBox::new(&(&self.center - &r), &(&self.center + &r))
+    }
+}
+
+/// Nearest root in `[t0, t1]` of the quadratic of FCG 4.4.1 for the ray
+/// `e + t d` and the sphere at `center` with radius `r`.
+fn solve_sphere<T: Scalar>(e: &Vec3<T>, d: &Vec3<T>, center: &Vec3<T>, r: T, t0: T, t1: T) -> Option<T> {
+    let j = e - center;
+    let a = d.dot(d);
+    let b = T::TWO * d.dot(&j);
+    let c = j.dot(&j) - r * r;
+    let discriminant = b * b - T::from_f64(4.) * a * c;
+    if discriminant < T::ZERO {
+        return None;
+    }
+
+    let near = (-b - discriminant.sqrt()) / (T::TWO * a);
+    let far = (-b + discriminant.sqrt()) / (T::TWO * a);
+    let t = if near >= t0 { near } else { far };
+    (t0..=t1).contains(&t).then_some(t)
+}
+
+/// Triangle surface, front facing where `a`, `b`, `c` are counter-clockwise.
+#[derive(PartialEq, Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TriangleSurface {
+    pub a: Vector3,
+    pub b: Vector3,
+    pub c: Vector3,
+    pub material: usize,
+}
+
+impl TriangleSurface {
+    pub fn new(a: Vector3, b: Vector3, c: Vector3, material: usize) -> Self {
+        Self { a, b, c, material }
+    }
+
+    pub fn geometry(&self) -> math::Triangle {
+        math::Triangle::new(self.a, self.b, self.c)
+    }
+}
+
+impl Surface for TriangleSurface {
+    /// Cramer's rule as in FCG 4.4.2, in double precision so slivers and
+    /// grazing rays do not divide by a determinant rounded to 0. `uv` are
+    /// the barycentric `(β, γ)`.
+    fn hit(&self, ray: &Ray, t0: f32, t1: f32) -> Option<HitRecord> {
+        let geometry = self.geometry().cast::<f64>();
+        let (e, d) = (ray.e.cast::<f64>(), ray.d.cast::<f64>());
+        let (t, beta, gamma) = geometry.intersect_ray(&e, &d, t0 as f64, t1 as f64)?;
+        let t = t as f32;
+
+        Some(HitRecord {
+            t,
+            point: ray.point(t),
+            normal: geometry.normal().cast(),
+            uv: Vector2::new(beta as f32, gamma as f32),
+            material: self.material,
+        })
+    }
+
+    fn bounding_box(&self) -> BoundingBox {
+        self.geometry().bounding_box()
+    }
+}
+
+/// The infinite plane through `point` with unit `normal`.
+#[derive(PartialEq, Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Plane {
+    pub point: Vector3,
+    pub normal: Vector3,
+    pub material: usize,
+}
+
+impl Plane {
+    pub fn new(point: Vector3, normal: &Vector3, material: usize) -> Self {
+        Self {
+            point,
+            normal: normal.normalize(),
+            material,
+        }
+    }
+}
+
+impl Surface for Plane {
+    /// `uv` are coordinates in the plane from `point`, along the tangents of
+    /// `OrthonormalBasis::from_normal`. Rays parallel to the plane never hit it.
+    fn hit(&self, ray: &Ray, t0: f32, t1: f32) -> Option<HitRecord> {
+        let denominator = ray.d.dot(&self.normal);
+        if denominator == 0. {
+            return None;
+        }
+
+        let t = (&self.point - &ray.e).dot(&self.normal) / denominator;
+        if !(t0..=t1).contains(&t) {
+            return None;
+        }
+
+        let point = ray.point(t);
+        let local = OrthonormalBasis::from_normal(&self.normal).to_local(&(&point - &self.point));
+
+        Some(HitRecord {
+            t,
+            point,
+            normal: self.normal,
+            uv: Vector2::new(local.x, local.y),
+            material: self.material,
+        })
+    }
+
+    /// Unbounded in every direction.
+    fn bounding_box(&self) -> BoundingBox {
+        let infinity = Vector3::new(f32::INFINITY, f32::INFINITY, f32::INFINITY);
+        BoundingBox::new(&-&infinity, &infinity)
+    }
+}
+
+/// Surfaces of any kind, hit as one (FCG 4.4.4).
+#[derive(Default)]
+pub struct SurfaceGroup {
+    surfaces: Vec<Box<dyn Surface>>,
+}
+
+impl SurfaceGroup {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, surface: impl Surface + 'static) {
+        self.surfaces.push(Box::new(surface));
+    }
+
+    pub fn push_boxed(&mut self, surface: Box<dyn Surface>) {
+        self.surfaces.push(surface);
+    }
+
+    pub fn len(&self) -> usize {
+        self.surfaces.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.surfaces.is_empty()
+    }
+
+    pub fn surfaces(&self) -> &[Box<dyn Surface>] {
+        &self.surfaces
+    }
+
+    pub fn into_surfaces(self) -> Vec<Box<dyn Surface>> {
+        self.surfaces
+    }
+}
+
+impl Surface for SurfaceGroup {
+    /// Tests every surface, narrowing `t1` to the closest hit so far.
+    fn hit(&self, ray: &Ray, t0: f32, t1: f32) -> Option<HitRecord> {
+        let mut closest = None;
+        let mut t1 = t1;
+        for surface in &self.surfaces {
+            if let Some(hit) = surface.hit(ray, t0, t1) {
+                t1 = hit.t;
+                closest = Some(hit);
+            }
+        }
+        closest
+    }
+
+    /// Stops at the first surface that blocks the ray.
+    fn any_hit(&self, ray: &Ray, t0: f32, t1: f32) -> bool {
+        self.surfaces.iter().any(|s| s.any_hit(ray, t0, t1))
+    }
+
+    fn bounding_box(&self) -> BoundingBox {
+        self.surfaces.iter().fold(BoundingBox::EMPTY, |b, s| b.union(&s.bounding_box()))
+    }
+}
+
+/// A light with `intensity` per color channel (FCG 4.5).
+#[derive(PartialEq, Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Light {
+    Point { position: Vector3, intensity: Color },
+    /// Infinitely far away, shining along `direction`.
+    Directional { direction: Vector3, intensity: Color },
+}
+
+impl Light {
+    pub fn intensity(&self) -> Color {
+        match self {
+            Light::Point { intensity, .. } | Light::Directional { intensity, .. } => *intensity,
+        }
+    }
+
+    /// Ray from `p` towards the light and the `t` at which it reaches it.
+    /// The direction is normalized, so `t` is the distance from `p`.
+    pub fn shadow_ray(&self, p: &Vector3) -> (Ray, f32) {
+        match self {
+            Light::Point { position, .. } => {
+                let d = position - p;
+                let distance = d.norm();
+                (Ray::new(*p, &d / distance), distance)
+            }
+            Light::Directional { direction, .. } => (Ray::new(*p, -&direction.normalize()), f32::INFINITY),
+        }
+    }
+}
+
+/// Surfaces and the lights shining on them, plus ambient light everywhere.
+#[derive(Default)]
+pub struct Scene {
+    pub surfaces: SurfaceGroup,
+    pub lights: Vec<Light>,
+    pub ambient: Color,
+}
+
+impl Scene {
+    /// Shadow rays start this far from the surface, in world units, so it
+    /// does not shadow itself through rounding error (FCG 4.7).
+    pub const EPSILON: f32 = 1e-4;
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn closest_hit(&self, ray: &Ray, t0: f32, t1: f32) -> Option<HitRecord> {
+        self.surfaces.hit(ray, t0, t1)
+    }
+
+    /// Whether anything blocks `ray` in `[t0, t1]`; cheaper than
+    /// `closest_hit` since it stops at the first blocker.
+    pub fn any_hit(&self, ray: &Ray, t0: f32, t1: f32) -> bool {
+        self.surfaces.any_hit(ray, t0, t1)
+    }
+
+    /// Whether `light` reaches `p` without being blocked.
+    pub fn is_lit(&self, p: &Vector3, light: &Light) -> bool {
+        let (ray, t1) = light.shadow_ray(p);
+        !self.any_hit(&ray, Self::EPSILON, t1)
+    }
+}
+
+/// `N` rays traced together, one per lane. Each lane evaluates the same
+/// expressions as a single ray in f32, so the results are identical to
+/// intersecting the rays one by one at that precision. The `Surface`
+/// implementations solve in f64 instead and can differ in the last bits.
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub struct RayPacket<const N: usize> {
+    pub e: Vec3xN<N>,
+    pub d: Vec3xN<N>,
+}
+
+impl<const N: usize> RayPacket<N> {
+    pub fn new(e: Vec3xN<N>, d: Vec3xN<N>) -> Self {
+        Self { e, d }
+    }
+
+    pub fn point(&self, t: &F32xN<N>) -> Vec3xN<N> {
+        &self.e + &(&self.d * t)
+    }
+
+    /// Nearest `t` in `[t0, t1]` where each ray hits the sphere, and which
+    /// lanes hit at all. `t` is meaningless in lanes that missed.
+    pub fn hit_sphere(&self, center: &Vector3, r: f32, t0: f32, t1: f32) -> (F32xN<N>, MaskN<N>) {
+        let j = &self.e - &Vec3xN::splat(center);
+        let a = self.d.dot(&self.d);
+        let b = 2. * &self.d.dot(&j);
+        let c = &j.dot(&j) - r * r;
+        let discriminant = &(&b * &b) - &(&(4. * &a) * &c);
+
+        let sqrt = discriminant.sqrt();
+        let near = &(&(-&b) - &sqrt) / &(2. * &a);
+        let far = &(&(-&b) + &sqrt) / &(2. * &a);
+        let t = F32xN::select(&near.ge(&F32xN::splat(t0)), &near, &far);
+
+        let hit = &(&discriminant.ge(&F32xN::splat(0.)) & &t.ge(&F32xN::splat(t0))) & &t.le(&F32xN::splat(t1));
+        (t, hit)
+    }
+
+    /// `t` in `[t0, t1]` where each ray hits triangle `abc`, by Cramer's rule
+    /// (FCG 4.4.2), and which lanes hit.
+    pub fn hit_triangle(&self, a: &Vector3, b: &Vector3, c: &Vector3, t0: f32, t1: f32) -> (F32xN<N>, MaskN<N>) {
+        let ae = &Vec3xN::splat(a) - &self.e;
+        let (a, b, c, d, e, f) = (a.x - b.x, a.y - b.y, a.z - b.z, a.x - c.x, a.y - c.y, a.z - c.z);
+        let (g, h, i) = (&self.d.x, &self.d.y, &self.d.z);
+        let (j, k, l) = (&ae.x, &ae.y, &ae.z);
+
+        let ei_hf = &(e * i) - &(h * f);
+        let gf_di = &(g * f) - &(d * i);
+        let dh_eg = &(d * h) - &(e * g);
+        let ak_jb = &(a * k) - &(j * b);
+        let jc_al = &(j * c) - &(a * l);
+        let bl_kc = &(b * l) - &(k * c);
+
+        let m = &(&(a * &ei_hf) + &(b * &gf_di)) + &(c * &dh_eg);
+        let beta = &(&(&(j * &ei_hf) + &(k * &gf_di)) + &(l * &dh_eg)) / &m;
+        let gamma = &(&(&(i * &ak_jb) + &(h * &jc_al)) + &(g * &bl_kc)) / &m;
+        let t = &(-&(&(&(f * &ak_jb) + &(e * &jc_al)) + &(d * &bl_kc))) / &m;
+
+        let zero = F32xN::splat(0.);
+        let in_t = &t.ge(&F32xN::splat(t0)) & &t.le(&F32xN::splat(t1));
+        let in_gamma = &gamma.ge(&zero) & &gamma.le(&F32xN::splat(1.));
+        let in_beta = &beta.ge(&zero) & &beta.le(&(1. - &gamma));
+        (t, &(&in_t & &in_gamma) & &in_beta)
+    }
+}
+
 #[test]
 fn test_ray_sphere_intersection() {
-    // sphere
-    let r = 1.0f32;
-    let o = Vector3::new(0.0, 0.0, 0.0);
+    use crate::math::vec3;
 
-    // ray
-    let e = Vector3::new(1., 1., 1.);
-    let d = Vector3::new(-1., -1., -1.);
+    let sphere = Sphere::new(vec3(0., 0., 0.), 1., 3);
+    let ray = Ray::new(vec3(1., 1., 1.), vec3(-1., -1., -1.));
 
-    let j = &e - &o;
-    let a = d.dot(&d);
-    let b = 2. * d.dot(&j);
-    let c = j.dot(&j) - r * r;
-    let discriminant = b * b - 4. * a * c;
+    let near = sphere.hit(&ray, 0., f32::INFINITY).expect("hit");
+    assert!((near.t - (1. - 1. / 3f32.sqrt())).abs() < 1e-6);
+    assert!((near.point.norm() - 1.).abs() < 1e-6);
+    assert_eq!(near.point, near.normal);
+    assert_eq!(3, near.material);
+
+    // starting past the near side finds the far one, facing away from the ray
+    let far = sphere.hit(&ray, near.t + 1e-3, f32::INFINITY).expect("hit");
+    assert!((far.t - (1. + 1. / 3f32.sqrt())).abs() < 1e-6);
+    assert!(far.normal.dot(&ray.d) > 0.);
 
-    println!("a:{}, b:{}, c:{}, discriminant:{}", a, b, c, discriminant);
-    assert!(discriminant > 0.);
+    assert_eq!(None, sphere.hit(&ray, 0., near.t / 2.));
+    assert_eq!(None, sphere.hit(&Ray::new(vec3(0., 2., 0.), vec3(1., 0., 0.)), 0., f32::INFINITY));
 
-    let t1 = (-b - discriminant.sqrt()) / (2. * a);
-    let t2 = (-b + discriminant.sqrt()) / (2. * a);
+    // longitude from +x, latitude from -z
+    let hit = sphere.hit(&Ray::new(vec3(2., 0., 0.), vec3(-1., 0., 0.)), 0., f32::INFINITY).unwrap();
+    assert!((hit.uv.x - 0.).abs() < 1e-6 && (hit.uv.y - 0.5).abs() < 1e-6);
+    let hit = sphere.hit(&Ray::new(vec3(0., -2., 0.), vec3(0., 1., 0.)), 0., f32::INFINITY).unwrap();
+    assert!((hit.uv.x - 0.75).abs() < 1e-6);
+    let hit = sphere.hit(&Ray::new(vec3(0., 0., 2.), vec3(0., 0., -1.)), 0., f32::INFINITY).unwrap();
+    assert_eq!(1., hit.uv.y);
 
-    println!("t1:{}, t2: {}", t1, t2);
+    assert_eq!(BoundingBox::new(&vec3(-1., -1., -1.), &vec3(1., 1., 1.)), sphere.bounding_box());
+}
 
-    let p1 = &e + &(t1 * &d);
-    let p2 = &e + &(t2 * &d);
-    println!("p1:{:?} p2:{:?}", p1, p2);
+#[test]
+fn test_ray_sphere_precision() {
+    use crate::math::vec3;
 
-    let p1 = p1.norm();
-    let p2 = p2.norm();
-    println!("p1 norm:{:?} p2 norm:{:?}", p1, p2);
+    // a ray leaving the big floor sphere from just above its top: f32 rounds
+    // |e - c|² - r² to 0 and finds a root at t = 0, f64 finds none ahead
+    let floor = Sphere::new(vec3(0., -5001., 0.), 5000., 0);
+    let (e, d) = (vec3(0., -0.9999, 0.), vec3(0., 1., 0.));
+    assert_eq!(Some(0.), solve_sphere(&e, &d, &floor.center, floor.radius, 0., f32::INFINITY));
+    assert_eq!(None, floor.hit(&Ray::new(e, d), 0., f32::INFINITY));
 
-    assert_eq!(p1.round(), 1., "p1");
-    assert_eq!(p2.round(), 1., "p2");
+    // and still hits it from above
+    let hit = floor.hit(&Ray::new(vec3(0., 1., 0.), vec3(0., -1., 0.)), 0., f32::INFINITY).unwrap();
+    assert!((hit.t - 2.).abs() < 1e-4);
+    assert!((&hit.normal - &vec3(0., 1., 0.)).norm() < 1e-6);
 }
 
 #[test]
 fn test_ray_triangle_intersection() {
+    use crate::math::vec3;
+
+    let triangle = TriangleSurface::new(vec3(1., 0., 0.), vec3(0., 1., 0.), vec3(0., 0., 1.), 1);
+    let ray = Ray::new(vec3(1., 1., 1.), vec3(-1., -1., -1.));
+
+    let hit = triangle.hit(&ray, 0., 1.).expect("hit");
+    assert!((hit.t - 2. / 3.).abs() < 1e-6);
+    assert!((&hit.point - &vec3(1. / 3., 1. / 3., 1. / 3.)).norm() < 1e-6);
+    assert!((&hit.normal - &vec3(1., 1., 1.).normalize()).norm() < 1e-6);
+    assert!((hit.uv.x - 1. / 3.).abs() < 1e-6 && (hit.uv.y - 1. / 3.).abs() < 1e-6);
+    assert_eq!(1, hit.material);
+
+    assert_eq!(None, triangle.hit(&ray, 0., 0.5));
+    assert_eq!(None, triangle.hit(&Ray::new(vec3(1., 1., 1.), vec3(-1., -1., 1.)), 0., f32::INFINITY));
+    assert_eq!(BoundingBox::new(&vec3(0., 0., 0.), &vec3(1., 1., 1.)), triangle.bounding_box());
+
+    // a sliver whose determinant underflows f32, seen edge-on
+    let sliver = TriangleSurface::new(vec3(0., 0., 0.), vec3(1e-25, 0., 0.), vec3(0., 1e-25, 0.), 0);
+    let ray = Ray::new(vec3(2e-26, 2e-26, 1.), vec3(0., 0., -1.));
+    assert_eq!(None, sliver.geometry().intersect_ray(&ray.e, &ray.d, 0., 2.));
+    let hit = sliver.hit(&ray, 0., 2.).expect("hit");
+    assert_eq!(1., hit.t);
+    assert!((hit.uv.x - 0.2).abs() < 1e-6 && (hit.uv.y - 0.2).abs() < 1e-6);
+    assert!(hit.normal.x.is_finite() && hit.normal.y.is_finite() && hit.normal.z.is_finite());
+    assert!((hit.normal.norm() - 1.).abs() < 1e-6);
+}
+
+#[test]
+fn test_ray_plane_intersection() {
+    use crate::math::vec3;
+
+    let plane = Plane::new(vec3(0., 1., 0.), &vec3(0., 2., 0.), 2);
 
+    let hit = plane.hit(&Ray::new(vec3(1., 3., 3.), vec3(0., -1., 0.)), 0., f32::INFINITY).expect("hit");
+    assert_eq!(2., hit.t);
+    assert_eq!(vec3(1., 1., 3.), hit.point);
+    assert_eq!(vec3(0., 1., 0.), hit.normal);
+    assert!((hit.uv.x.hypot(hit.uv.y) - 10f32.sqrt()).abs() < 1e-6);
+
+    // from below, parallel and behind
+    assert!(plane.hit(&Ray::new(vec3(0., 0., 0.), vec3(1., 1., 0.)), 0., f32::INFINITY).is_some());
+    assert_eq!(None, plane.hit(&Ray::new(vec3(0., 3., 0.), vec3(1., 0., 0.)), 0., f32::INFINITY));
+    assert_eq!(None, plane.hit(&Ray::new(vec3(0., 3., 0.), vec3(0., 1., 0.)), 0., f32::INFINITY));
+    assert!(plane.bounding_box().contains(&vec3(1e30, -1e30, 0.)));
+}
+
+#[test]
+fn test_surface_group() {
     use crate::math::vec3;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    // counts how often it is asked, to check that any_hit stops early
+    struct Counted<S>(S, Rc<Cell<usize>>);
+
+    impl<S: Surface> Surface for Counted<S> {
+        fn hit(&self, ray: &Ray, t0: f32, t1: f32) -> Option<HitRecord> {
+            self.1.set(self.1.get() + 1);
+            self.0.hit(ray, t0, t1)
+        }
+
+        fn bounding_box(&self) -> BoundingBox {
+            self.0.bounding_box()
+        }
+    }
+
+    let mut group = SurfaceGroup::new();
+    group.push(Plane::new(vec3(0., -1., 0.), &vec3(0., 1., 0.), 0));
+    group.push(Sphere::new(vec3(0., 0., -10.), 1., 1));
+    group.push(Sphere::new(vec3(0., 0., -5.), 1., 2));
+    group.push(TriangleSurface::new(vec3(-1., -1., -20.), vec3(1., -1., -20.), vec3(0., 1., -20.), 3));
+    assert_eq!(4, group.len());
+
+    // the nearest sphere, whatever the order
+    let ray = Ray::new(vec3(0., 0., 0.), vec3(0., 0., -1.));
+    let hit = group.hit(&ray, 0., f32::INFINITY).expect("hit");
+    assert_eq!((4., 2), (hit.t, hit.material));
+    assert_eq!(3, group.hit(&ray, 12., f32::INFINITY).unwrap().material);
+    assert_eq!(None, group.hit(&ray, 0., 3.));
+
+    // down onto the plane
+    let hit = group.hit(&Ray::new(vec3(0., 0., 0.), vec3(0., -1., 0.)), 0., f32::INFINITY).unwrap();
+    assert_eq!((1., 0), (hit.t, hit.material));
+
+    let mut group = SurfaceGroup::new();
+    let counts = [(); 3].map(|_| Rc::new(Cell::new(0)));
+    for (z, count) in [-5., -10., -15.].into_iter().zip(&counts) {
+        group.push(Counted(Sphere::new(vec3(0., 0., z), 1., 0), count.clone()));
+    }
+    let count = |i: usize| counts[i].get();
+
+    assert!(group.any_hit(&ray, 0., f32::INFINITY));
+    assert_eq!([1, 0, 0], [count(0), count(1), count(2)]);
+    group.hit(&ray, 0., f32::INFINITY);
+    assert_eq!([2, 1, 1], [count(0), count(1), count(2)]);
+
+    assert_eq!(BoundingBox::new(&vec3(-1., -1., -16.), &vec3(1., 1., -4.)), group.bounding_box());
+}
+
+#[test]
+fn test_shadows() {
+    use crate::math::vec3;
+
+    // a sphere resting above a floor, lit from straight above
+    let mut scene = Scene::new();
+    scene.surfaces.push(Plane::new(vec3(0., 0., 0.), &vec3(0., 1., 0.), 0));
+    scene.surfaces.push(Sphere::new(vec3(0., 2., 0.), 1., 1));
+
+    let white = Color::WHITE;
+    let point = Light::Point { position: vec3(0., 10., 0.), intensity: white };
+    let sun = Light::Directional { direction: vec3(-1., -1., 0.), intensity: white };
+    scene.lights = vec![point, sun];
+
+    let under = vec3(0., 0., 0.);
+    let aside = vec3(5., 0., 0.);
+    assert!(!scene.is_lit(&under, &point));
+    assert!(scene.is_lit(&aside, &point));
+
+    // the sun comes in at 45° from +x, so the shadow falls towards -x
+    assert!(!scene.is_lit(&vec3(-2., 0., 0.), &sun));
+    assert!(scene.is_lit(&vec3(2., 0., 0.), &sun));
+
+    // the top of the sphere is lit, the light itself never blocks
+    let top = scene.closest_hit(&Ray::new(vec3(0., 5., 0.), vec3(0., -1., 0.)), 0., f32::INFINITY).unwrap();
+    assert_eq!(vec3(0., 3., 0.), top.point);
+    assert!(scene.is_lit(&top.point, &point));
+
+    // a light below the floor
+    let below = Light::Point { position: vec3(0., -1., 0.), intensity: white };
+    assert!(!scene.is_lit(&vec3(3., 1., 0.), &below));
+    assert_eq!(white, below.intensity());
+
+    // the offset does not grow with the distance to the light: a far away
+    // light is still blocked by the sphere a unit above the floor
+    let far = Light::Point { position: vec3(0., 1e5, 0.), intensity: white };
+    let (ray, t1) = far.shadow_ray(&vec3(0.5, 0., 0.));
+    assert!((ray.d.norm() - 1.).abs() < 1e-6);
+    assert!((t1 - 1e5).abs() < 1.);
+    assert!(!scene.is_lit(&vec3(0.5, 0., 0.), &far));
+    assert!(scene.is_lit(&aside, &far));
+}
+
+#[test]
+fn test_ray_packet_sphere_intersection() {
+    use crate::math::{vec3, Vector3x4};
+
+    let sphere = Sphere::new(vec3(0.5, 0., 0.), 1., 0);
+
+    // rays: through the center, grazing, missing and starting inside
+    let e = [vec3(1., 1., 1.), vec3(0.5, 1., 2.), vec3(3., 3., 3.), vec3(0.5, 0., 0.)];
+    let d = [vec3(-1., -1., -1.), vec3(0., 0., -1.), vec3(1., 0., 0.), vec3(0., 1., 0.)];
+
+    let packet = RayPacket::new(Vector3x4::from_lanes(&e), Vector3x4::from_lanes(&d));
+    let (t, hit) = packet.hit_sphere(&sphere.center, sphere.radius, 0., 10.);
+    assert_eq!([true, true, false, true], hit.0);
+
+    for lane in 0..4 {
+        let scalar = solve_sphere(&e[lane], &d[lane], &sphere.center, sphere.radius, 0., 10.);
+
+        assert_eq!(scalar.is_some(), hit.0[lane], "lane {lane}");
+        if let Some(scalar) = scalar {
+            assert_eq!(scalar, t.0[lane], "lane {lane}");
+            assert_eq!(Ray::new(e[lane], d[lane]).point(scalar), packet.point(&t).lane(lane));
+        }
+    }
+}
+
+#[test]
+fn test_ray_packet_triangle_intersection() {
+    use crate::math::{vec3, Vector3x8};
+
+    let triangle = TriangleSurface::new(vec3(1., 0., 0.), vec3(0., 1., 0.), vec3(0., 0., 1.), 0);
+
+    // rays from (1, 1, 1) towards points inside, on the edge of and outside the triangle
+    let targets = [
+        vec3(1. / 3., 1. / 3., 1. / 3.),
+        vec3(0.5, 0.5, 0.),
+        vec3(0.8, 0.1, 0.1),
+        vec3(1., 1., -1.),
+        vec3(-0.5, 0.5, 1.),
+        vec3(0.2, 0.3, 0.5),
+        vec3(0., 0., 0.),
+        vec3(2., 2., 2.),
+    ];
+    let e = [vec3(1., 1., 1.); 8];
+    let d = targets.map(|p| &p - &e[0]);
+
+    let packet = RayPacket::new(Vector3x8::from_lanes(&e), Vector3x8::from_lanes(&d));
+    let (t, hit) = packet.hit_triangle(&triangle.a, &triangle.b, &triangle.c, 0., 2.);
+    assert_eq!([true, true, true, false, false, true, true, false], hit.0);
+
+    for lane in 0..8 {
+        let scalar = triangle.geometry().intersect_ray(&e[lane], &d[lane], 0., 2.);
 
-    // ray
-    let ray = Ray {
-        e: vec3(1., 1., 1.),
-        d: vec3(-1., -1., -1.),
-    };
-
-    // triangle
-    let a = vec3(1., 0., 0.);
-    let b = vec3(0., 1., 0.);
-    let c = vec3(0., 0., 1.);
-
-    {
-        let ((a, b, c), (d, e, f), (g, h, i), (j, k, l)) = (
-            (a.x - b.x, a.y - b.y, a.z - b.z),
-            (a.x - c.x, a.y - c.y, a.z - c.z),
-            (ray.d.x, ray.d.y, ray.d.z),
-            (a.x - ray.e.x, a.y - ray.e.y, a.z - ray.e.z),
-        );
-
-        let m = a * (e * i - h * f) + b * (g * f - d * i) + c * (d * h - e * g);
-        let beta = (j * (e * i - h * f) + k * (g * f - d * i) + l * (d * h - e * g)) / m;
-        let gamma = (i * (a * k - j * b) + h * (j * c - a * l) + g * (b * l - k * c)) / m;
-        let alpha = 1. - beta - gamma;
-        let t = -(f * (a * k - j * b) + e * (j * c - a * l) + d * (b * l - k * c)) / m;
-        let p = ray.point(t);
-
-        println!("alpha:{alpha}, beta:{beta}, gamma:{gamma}, t:{t}");
-        println!("p:{:?}", p);
-
-        let (t0, t1) = (0., 1.);
-
-        let hit = if t < t0 || t > t1 {
-            false
-        } else if gamma < 0. || gamma > 1. {
-            false
-        } else if beta < 0. || beta > 1. - gamma {
-            false
-        } else {
-            true
-        };
-
-        assert_eq!(hit, true);
+        assert_eq!(scalar.is_some(), hit.0[lane], "lane {lane}");
+        if let Some((scalar, _, _)) = scalar {
+            assert_eq!(scalar, t.0[lane], "lane {lane}");
+        }
     }
 }
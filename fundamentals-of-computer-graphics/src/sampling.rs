@@ -0,0 +1,272 @@
+//! Sample generation and warping for antialiasing and Monte Carlo
+//! integration. Everything is deterministic: the same seed or sample index
+//! always gives bit-identical samples.
+//!
+//! Warps take a point in `[0, 1)²` and return directions around +z; use
+//! `OrthonormalBasis::from_normal` to orient them around a surface normal.
+
+use crate::math::{Vector2, Vector3};
+use std::f32::consts::{FRAC_1_PI, FRAC_PI_2, FRAC_PI_4, PI};
+
+/// `2^-24`, turns the top 24 bits of a `u32` into an `f32` in `[0, 1)`.
+const U32_TO_UNIT: f32 = 1. / (1u32 << 24) as f32;
+
+/// PCG32 random number generator (O'Neill 2014), small and seedable.
+#[derive(PartialEq, Debug, Clone)]
+pub struct Rng {
+    state: u64,
+    inc: u64,
+}
+
+impl Rng {
+    const MULTIPLIER: u64 = 6364136223846793005;
+
+    pub fn new(seed: u64) -> Self {
+        Self::with_stream(seed, 0xda3e39cb94b95bdb)
+    }
+
+    /// Generators with the same seed but different streams give independent
+    /// sequences, e.g. one per pixel or per thread.
+    pub fn with_stream(seed: u64, stream: u64) -> Self {
+        let mut rng = Self { state: 0, inc: (stream << 1) | 1 };
+        rng.next_u32();
+        rng.state = rng.state.wrapping_add(seed);
+        rng.next_u32();
+        rng
+    }
+
+    pub fn next_u32(&mut self) -> u32 {
+        let old = self.state;
+        self.state = old.wrapping_mul(Self::MULTIPLIER).wrapping_add(self.inc);
+        let xorshifted = (((old >> 18) ^ old) >> 27) as u32;
+        xorshifted.rotate_right((old >> 59) as u32)
+    }
+
+    /// Uniform in `[0, 1)`.
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u32() >> 8) as f32 * U32_TO_UNIT
+    }
+
+    /// Uniform in `[0, 1)²`.
+    pub fn next_vector2(&mut self) -> Vector2 {
+        let x = self.next_f32();
+        Vector2::new(x, self.next_f32())
+    }
+}
+
+/// Centers of an `nx` × `ny` grid over `[0, 1)²`, row by row.
+pub fn stratified(nx: usize, ny: usize) -> Vec<Vector2> {
+    grid(nx, ny, || Vector2::new(0.5, 0.5))
+}
+
+/// One random sample in each cell of an `nx` × `ny` grid over `[0, 1)²`,
+/// row by row (FCG 13.4.1).
+pub fn jittered(nx: usize, ny: usize, rng: &mut Rng) -> Vec<Vector2> {
+    grid(nx, ny, || rng.next_vector2())
+}
+
+fn grid(nx: usize, ny: usize, mut offset: impl FnMut() -> Vector2) -> Vec<Vector2> {
+    (0..nx * ny)
+        .map(|k| {
+            let o = offset();
+            let (i, j) = (k % nx, k / nx);
+            Vector2::new(
+                ((i as f32 + o.x) / nx as f32).min(1. - f32::EPSILON / 2.),
+                ((j as f32 + o.y) / ny as f32).min(1. - f32::EPSILON / 2.),
+            )
+        })
+        .collect()
+}
+
+/// The digits of `i` in `base` mirrored around the radix point.
+pub fn radical_inverse(mut i: u64, base: u32) -> f32 {
+    let base = base as u64;
+    let inv_base = 1. / base as f64;
+    let (mut reversed, mut inv_base_n) = (0u64, 1f64);
+    while i > 0 {
+        reversed = reversed * base + i % base;
+        inv_base_n *= inv_base;
+        i /= base;
+    }
+    ((reversed as f64 * inv_base_n) as f32).min(1. - f32::EPSILON / 2.)
+}
+
+/// `i`-th point of the 2D Halton sequence, bases 2 and 3.
+pub fn halton(i: u64) -> Vector2 {
+    Vector2::new(radical_inverse(i, 2), radical_inverse(i, 3))
+}
+
+/// `i`-th point of the 2D Sobol sequence. Every power-of-two prefix has one
+/// point in each elementary interval of its size, so it stratifies at any
+/// sample count.
+pub fn sobol(i: u32) -> Vector2 {
+    let (mut x, mut y, mut v) = (0u32, 0u32, 1u32 << 31);
+    let mut bits = i;
+    let mut k = 0;
+    while bits != 0 {
+        if bits & 1 != 0 {
+            x ^= 1 << (31 - k);
+            y ^= v;
+        }
+        bits >>= 1;
+        v ^= v >> 1;
+        k += 1;
+    }
+    Vector2::new((x >> 8) as f32 * U32_TO_UNIT, (y >> 8) as f32 * U32_TO_UNIT)
+}
+
+/// Uniform point in the unit disk, Shirley and Chiu's concentric mapping
+/// which keeps strata compact.
+pub fn uniform_disk(u: &Vector2) -> Vector2 {
+    let (a, b) = (2. * u.x - 1., 2. * u.y - 1.);
+    if a == 0. && b == 0. {
+        return Vector2::new(0., 0.);
+    }
+
+    let (r, phi) = if a.abs() > b.abs() {
+        (a, FRAC_PI_4 * (b / a))
+    } else {
+        (b, FRAC_PI_2 - FRAC_PI_4 * (a / b))
+    };
+    let (sin, cos) = phi.sin_cos();
+    Vector2::new(r * cos, r * sin)
+}
+
+pub fn uniform_disk_pdf() -> f32 {
+    FRAC_1_PI
+}
+
+/// Uniform direction on the unit sphere.
+pub fn uniform_sphere(u: &Vector2) -> Vector3 {
+    let z = 1. - 2. * u.x;
+    let r = (1. - z * z).max(0.).sqrt();
+    let (sin, cos) = (2. * PI * u.y).sin_cos();
+    Vector3::new(r * cos, r * sin, z)
+}
+
+pub fn uniform_sphere_pdf() -> f32 {
+    1. / (4. * PI)
+}
+
+/// Uniform direction on the hemisphere around +z.
+pub fn uniform_hemisphere(u: &Vector2) -> Vector3 {
+    let z = u.x;
+    let r = (1. - z * z).max(0.).sqrt();
+    let (sin, cos) = (2. * PI * u.y).sin_cos();
+    Vector3::new(r * cos, r * sin, z)
+}
+
+pub fn uniform_hemisphere_pdf() -> f32 {
+    1. / (2. * PI)
+}
+
+/// Direction around +z with density proportional to the cosine of its
+/// angle to +z, by projecting a disk sample up (Malley's method).
+pub fn cosine_hemisphere(u: &Vector2) -> Vector3 {
+    let d = uniform_disk(u);
+    Vector3::new(d.x, d.y, (1. - d.x * d.x - d.y * d.y).max(0.).sqrt())
+}
+
+pub fn cosine_hemisphere_pdf(cos_theta: f32) -> f32 {
+    cos_theta.max(0.) * FRAC_1_PI
+}
+
+#[cfg(test)]
+mod test_sampling {
+    use super::*;
+
+    #[test]
+    fn test_rng() {
+        // Reference output of the PCG32 demo, pcg32_srandom(42, 54).
+        let mut rng = Rng::with_stream(42, 54);
+        let expected = [0xa15c02b7, 0x7b47f409, 0xba1d3330, 0x83d2f293, 0xbfa4784b, 0xcbed606e];
+        assert_eq!(expected, [(); 6].map(|_| rng.next_u32()));
+
+        let (mut a, mut b) = (Rng::new(7), Rng::new(7));
+        for _ in 0..1000 {
+            let (x, y) = (a.next_f32(), b.next_f32());
+            assert_eq!(x.to_bits(), y.to_bits());
+            assert!((0. ..1.).contains(&x));
+        }
+        assert_ne!(Rng::new(7).next_u32(), Rng::new(8).next_u32());
+        assert_ne!(Rng::with_stream(7, 1).next_u32(), Rng::with_stream(7, 2).next_u32());
+    }
+
+    #[test]
+    fn test_stratified() {
+        assert_eq!(vec![Vector2::new(0.25, 0.5), Vector2::new(0.75, 0.5)], stratified(2, 1));
+
+        let samples = jittered(4, 3, &mut Rng::new(1));
+        assert_eq!(12, samples.len());
+        for (k, s) in samples.iter().enumerate() {
+            assert_eq!(((k % 4) as f32, (k / 4) as f32), ((s.x * 4.).floor(), (s.y * 3.).floor()));
+        }
+        assert_eq!(samples, jittered(4, 3, &mut Rng::new(1)));
+    }
+
+    #[test]
+    fn test_halton() {
+        assert_eq!([0., 0.5, 0.25, 0.75, 0.125], [0, 1, 2, 3, 4].map(|i| radical_inverse(i, 2)));
+        assert_eq!([1. / 3., 2. / 3., 1. / 9.], [1, 2, 3].map(|i| radical_inverse(i, 3)));
+        assert_eq!(Vector2::new(0.75, 1. / 9.), halton(3));
+    }
+
+    #[test]
+    fn test_sobol() {
+        let points: Vec<_> = (0..4).map(sobol).collect();
+        assert_eq!(
+            vec![Vector2::new(0., 0.), Vector2::new(0.5, 0.5), Vector2::new(0.25, 0.75), Vector2::new(0.75, 0.25)],
+            points
+        );
+
+        // The first 16 points fill each cell of a 4 x 4 grid exactly once.
+        let mut cells = [0; 16];
+        for p in (0..16).map(sobol) {
+            cells[(p.x * 4.) as usize + 4 * (p.y * 4.) as usize] += 1;
+        }
+        assert_eq!([1; 16], cells);
+    }
+
+    #[test]
+    fn test_warps() {
+        let mut rng = Rng::new(3);
+        for _ in 0..1000 {
+            let u = rng.next_vector2();
+
+            assert!(uniform_disk(&u).norm() <= 1.);
+            assert!((uniform_sphere(&u).norm() - 1.).abs() < 1e-6);
+            let h = uniform_hemisphere(&u);
+            assert!((h.norm() - 1.).abs() < 1e-6 && h.z >= 0.);
+            let c = cosine_hemisphere(&u);
+            assert!((c.norm() - 1.).abs() < 1e-6 && c.z >= 0.);
+        }
+
+        assert_eq!(Vector2::new(0., 0.), uniform_disk(&Vector2::new(0.5, 0.5)));
+        assert_eq!(Vector3::new(0., 0., 1.), uniform_sphere(&Vector2::new(0., 0.)));
+    }
+
+    #[test]
+    fn test_pdfs() {
+        // Monte Carlo estimates of the integral of cos θ over the hemisphere,
+        // which is π.
+        let n = 4096;
+        let samples: Vec<_> = (0..n).map(sobol).collect();
+
+        let uniform: f32 = samples.iter().map(|u| uniform_hemisphere(u).z / uniform_hemisphere_pdf()).sum::<f32>() / n as f32;
+        assert!((uniform - PI).abs() < 1e-2, "{uniform}");
+
+        let cosine: f32 = samples
+            .iter()
+            .map(cosine_hemisphere)
+            .filter(|d| d.z > 0.)
+            .map(|d| d.z / cosine_hemisphere_pdf(d.z))
+            .sum::<f32>()
+            / n as f32;
+        assert!((cosine - PI).abs() < 1e-2, "{cosine}");
+
+        // The fraction of sphere samples in the upper hemisphere matches the
+        // ratio of the pdfs.
+        let upper = samples.iter().filter(|u| uniform_sphere(u).z > 0.).count() as f32 / n as f32;
+        assert!((upper - uniform_sphere_pdf() / uniform_hemisphere_pdf()).abs() < 1e-2);
+    }
+}
@@ -0,0 +1,298 @@
+//! Ear-clipping triangulation of simple polygons, following Eberly,
+//! "Triangulation by Ear Clipping". Holes are first joined to the outer
+//! boundary by a pair of coincident bridge edges, turning the polygon with
+//! holes into a single weakly simple one.
+//!
+//! Triangles are index triples, counter-clockwise whatever the orientation of
+//! the input, and never degenerate: vertices lying on a straight edge end up
+//! in a neighbouring triangle instead of a zero-area one.
+
+use crate::math::{orient2d, Vector2};
+
+/// Triangulates a simple polygon given in either orientation. A polygon with
+/// `n` vertices, none of them collinear, gives `n - 2` triangles.
+pub fn triangulate(polygon: &[Vector2]) -> Vec<[usize; 3]> {
+    triangulate_with_holes(polygon, &[])
+}
+
+/// Triangulates `outer` minus the `holes`, which must lie strictly inside it
+/// and not touch each other. Indices refer to the vertices of `outer`
+/// followed by those of each hole in turn.
+pub fn triangulate_with_holes(outer: &[Vector2], holes: &[&[Vector2]]) -> Vec<[usize; 3]> {
+    let points: Vec<Vector2> = outer.iter().chain(holes.iter().flat_map(|h| h.iter())).copied().collect();
+
+    let mut polygon = oriented(&points, 0..outer.len(), true);
+
+    let mut start = outer.len();
+    let mut rings = vec![];
+    for hole in holes {
+        rings.push(oriented(&points, start..start + hole.len(), false));
+        start += hole.len();
+    }
+
+    // rightmost holes first, so each bridge only has to see the boundary
+    let rightmost = |ring: &Vec<usize>| ring.iter().map(|&i| points[i].x).fold(f32::NEG_INFINITY, f32::max);
+    rings.sort_by(|a, b| rightmost(b).total_cmp(&rightmost(a)));
+    for ring in rings {
+        if !ring.is_empty() {
+            bridge(&points, &mut polygon, &ring);
+        }
+    }
+
+    clip_ears(&points, polygon)
+}
+
+fn signed_area(points: &[Vector2], ring: &[usize]) -> f32 {
+    let n = ring.len();
+    (0..n).map(|k| points[ring[k]].cross(&points[ring[(k + 1) % n]])).sum::<f32>() / 2.
+}
+
+/// Indices in `range`, counter-clockwise if `ccw`, else clockwise.
+fn oriented(points: &[Vector2], range: std::ops::Range<usize>, ccw: bool) -> Vec<usize> {
+    let mut ring: Vec<usize> = range.collect();
+    if (signed_area(points, &ring) > 0.) != ccw {
+        ring.reverse();
+    }
+    ring
+}
+
+/// Whether `p` is inside or on the boundary of the counter-clockwise
+/// triangle `abc`.
+fn in_triangle(a: &Vector2, b: &Vector2, c: &Vector2, p: &Vector2) -> bool {
+    orient2d(a, b, p) >= 0. && orient2d(b, c, p) >= 0. && orient2d(c, a, p) >= 0.
+}
+
+/// Whether `p` lies in the interior angle at `b` between edges `ab` and `bc`
+/// of a counter-clockwise polygon.
+fn in_cone(a: &Vector2, b: &Vector2, c: &Vector2, p: &Vector2) -> bool {
+    if orient2d(a, b, c) >= 0. {
+        orient2d(a, b, p) > 0. && orient2d(b, c, p) > 0.
+    } else {
+        orient2d(a, b, p) > 0. || orient2d(b, c, p) > 0.
+    }
+}
+
+/// Splices the clockwise `hole` into the counter-clockwise `polygon` through
+/// an edge from the hole's rightmost vertex `m` to a visible vertex `p` of the
+/// polygon, walking `.., p, m, hole.., m, p, ..`.
+fn bridge(points: &[Vector2], polygon: &mut Vec<usize>, hole: &[usize]) {
+    let start = (0..hole.len()).max_by(|&i, &j| points[hole[i]].x.total_cmp(&points[hole[j]].x)).unwrap();
+    let m = points[hole[start]];
+    let n = polygon.len();
+
+    // nearest edge crossed by the ray from m towards +x
+    let mut nearest: Option<(f32, usize)> = None;
+    for k in 0..n {
+        let (a, b) = (points[polygon[k]], points[polygon[(k + 1) % n]]);
+        if (a.y > m.y) == (b.y > m.y) && a.y != m.y {
+            continue;
+        }
+
+        let x = if a.y == b.y { a.x.min(b.x) } else { a.x + (m.y - a.y) / (b.y - a.y) * (b.x - a.x) };
+        if x >= m.x && nearest.is_none_or(|(best, _)| x < best) {
+            nearest = Some((x, k));
+        }
+    }
+    let Some((x, k)) = nearest else { return };
+
+    // the edge endpoint furthest right is visible unless a reflex vertex
+    // lies inside the triangle between it, m and the crossing; then the one
+    // closest in angle to the ray is
+    let (a, b) = (polygon[k], polygon[(k + 1) % n]);
+    let mut target = if points[a].x > points[b].x { a } else { b };
+    let crossing = Vector2::new(x, m.y);
+    if crossing != points[target] {
+        let p = points[target];
+        let (u, v) = if orient2d(&m, &crossing, &p) > 0. { (crossing, p) } else { (p, crossing) };
+
+        let mut best = f32::NEG_INFINITY;
+        for k in 0..n {
+            let (prev, i, next) = (polygon[(k + n - 1) % n], polygon[k], polygon[(k + 1) % n]);
+            let r = points[i];
+            if orient2d(&points[prev], &r, &points[next]) < 0. && r != p && in_triangle(&m, &u, &v, &r) {
+                let d = &r - &m;
+                let cos = d.x / d.norm();
+                if cos > best {
+                    best = cos;
+                    target = i;
+                }
+            }
+        }
+    }
+
+    // a vertex visited twice by earlier bridges must be entered from the
+    // copy whose interior angle faces m
+    let position = (0..n)
+        .filter(|&k| polygon[k] == target)
+        .find(|&k| in_cone(&points[polygon[(k + n - 1) % n]], &points[target], &points[polygon[(k + 1) % n]], &m))
+        .or_else(|| polygon.iter().position(|&i| i == target))
+        .unwrap();
+
+    let mut splice = Vec::with_capacity(hole.len() + 2);
+    splice.extend(hole[start..].iter().chain(&hole[..start]));
+    splice.push(hole[start]);
+    splice.push(target);
+    polygon.splice(position + 1..position + 1, splice);
+}
+
+fn clip_ears(points: &[Vector2], mut polygon: Vec<usize>) -> Vec<[usize; 3]> {
+    let mut triangles = Vec::with_capacity(polygon.len().saturating_sub(2));
+
+    let is_ear = |polygon: &[usize], k: usize| {
+        let n = polygon.len();
+        let [a, b, c] = [polygon[(k + n - 1) % n], polygon[k], polygon[(k + 1) % n]].map(|i| points[i]);
+        if orient2d(&a, &b, &c) <= 0. {
+            return false;
+        }
+
+        // bridge copies share a position with the ear's own corners
+        polygon.iter().map(|&i| points[i]).all(|p| p == a || p == b || p == c || !in_triangle(&a, &b, &c, &p))
+    };
+
+    let mut k = 0;
+    let mut misses = 0;
+    while polygon.len() > 3 {
+        let n = polygon.len();
+        k %= n;
+
+        if is_ear(&polygon, k) {
+            triangles.push([polygon[(k + n - 1) % n], polygon[k], polygon[(k + 1) % n]]);
+            polygon.remove(k);
+            k = (k + n - 2) % (n - 1);
+            misses = 0;
+            continue;
+        }
+
+        k += 1;
+        misses += 1;
+        if misses < n {
+            continue;
+        }
+
+        // No ear left, which only happens with degenerate input. Dropping a
+        // vertex on a straight edge loses no area; failing that the polygon
+        // self-intersects and the rest is left untriangulated.
+        let straight = (0..n).find(|&k| {
+            let [a, b, c] = [polygon[(k + n - 1) % n], polygon[k], polygon[(k + 1) % n]].map(|i| points[i]);
+            orient2d(&a, &b, &c) == 0.
+        });
+        match straight {
+            Some(s) => {
+                polygon.remove(s);
+                misses = 0;
+            }
+            None => return triangles,
+        }
+    }
+
+    if let [a, b, c] = polygon[..]
+        && orient2d(&points[a], &points[b], &points[c]) > 0.
+    {
+        triangles.push([a, b, c]);
+    }
+    triangles
+}
+
+#[cfg(test)]
+mod test_triangulation {
+    use super::*;
+    use crate::math::vec2;
+
+    fn area(points: &[Vector2], triangles: &[[usize; 3]]) -> f32 {
+        triangles
+            .iter()
+            .map(|&[a, b, c]| {
+                let twice = orient2d(&points[a], &points[b], &points[c]);
+                assert!(twice > 0., "{:?} is not counter-clockwise", [a, b, c]);
+                twice as f32 / 2.
+            })
+            .sum()
+    }
+
+    #[test]
+    fn test_convex() {
+        let square = [vec2(0., 0.), vec2(1., 0.), vec2(1., 1.), vec2(0., 1.)];
+
+        let triangles = triangulate(&square);
+        assert_eq!(2, triangles.len());
+        assert_eq!(1., area(&square, &triangles));
+
+        // clockwise input still gives counter-clockwise triangles
+        let reversed: Vec<_> = square.iter().rev().copied().collect();
+        assert_eq!(1., area(&reversed, &triangulate(&reversed)));
+
+        assert!(triangulate(&square[..2]).is_empty());
+    }
+
+    #[test]
+    fn test_concave() {
+        // a comb whose teeth only leave one ear at a time near the notches
+        let comb = [
+            vec2(0., 0.),
+            vec2(5., 0.),
+            vec2(5., 3.),
+            vec2(4., 3.),
+            vec2(4., 1.),
+            vec2(3., 1.),
+            vec2(3., 3.),
+            vec2(2., 3.),
+            vec2(2., 1.),
+            vec2(1., 1.),
+            vec2(1., 3.),
+            vec2(0., 3.),
+        ];
+        let triangles = triangulate(&comb);
+        assert_eq!(comb.len() - 2, triangles.len());
+        assert_eq!(5. + 3. * 2., area(&comb, &triangles));
+
+        let arrow = [vec2(0., 0.), vec2(4., 2.), vec2(0., 4.), vec2(1., 2.)];
+        let triangles = triangulate(&arrow);
+        assert_eq!(2, triangles.len());
+        assert_eq!(6., area(&arrow, &triangles));
+    }
+
+    #[test]
+    fn test_collinear() {
+        // extra vertices along two edges of a square
+        let square = [
+            vec2(0., 0.),
+            vec2(1., 0.),
+            vec2(2., 0.),
+            vec2(3., 0.),
+            vec2(3., 3.),
+            vec2(0., 3.),
+            vec2(0., 2.),
+            vec2(0., 1.),
+        ];
+        let triangles = triangulate(&square);
+        assert_eq!(9., area(&square, &triangles));
+
+        // every vertex is used, so there are no T-junctions along the edges
+        for i in 0..square.len() {
+            assert!(triangles.iter().any(|t| t.contains(&i)), "{i}");
+        }
+
+        // all vertices on a line
+        assert!(triangulate(&[vec2(0., 0.), vec2(1., 1.), vec2(2., 2.)]).is_empty());
+    }
+
+    #[test]
+    fn test_holes() {
+        let outer = [vec2(0., 0.), vec2(10., 0.), vec2(10., 10.), vec2(0., 10.)];
+        let hole = [vec2(2., 2.), vec2(2., 4.), vec2(4., 4.), vec2(4., 2.)];
+        let points = [&outer[..], &hole[..]].concat();
+
+        let triangles = triangulate_with_holes(&outer, &[&hole]);
+        assert_eq!(outer.len() + hole.len(), triangles.len());
+        assert_eq!(100. - 4., area(&points, &triangles));
+
+        // two holes side by side, the left one bridged through the right one
+        let left = [vec2(1., 4.), vec2(3., 4.), vec2(3., 6.), vec2(1., 6.)];
+        let right = [vec2(5., 4.), vec2(7., 5.), vec2(5., 6.)];
+        let points = [&outer[..], &left[..], &right[..]].concat();
+
+        let triangles = triangulate_with_holes(&outer, &[&left, &right]);
+        assert_eq!(outer.len() + left.len() + right.len() + 2, triangles.len());
+        assert_eq!(100. - 4. - 2., area(&points, &triangles));
+    }
+}